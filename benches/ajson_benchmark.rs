@@ -70,19 +70,18 @@ fn ajson_selector(json: &str) {
 
 fn ajson_multi_query(json: &str) {
     black_box([
-        ajson::get(json, "widget.image.src"),
-        ajson::get(json, "widget.text.data"),
+        ajson::get(json, "widget.image.src").unwrap(),
+        ajson::get(json, "widget.text.data").unwrap(),
     ]);
 }
 
+#[allow(dead_code)] // disabled in `criterion_benchmark`
 fn ajson_path_bench() {
-    black_box(ajson::Path::from_slice("widget.window.name".as_bytes()));
-    black_box(ajson::Path::from_slice("widget.image.hOffset".as_bytes()));
-    black_box(ajson::Path::from_slice("widget.text.onMouseUp".as_bytes()));
-    black_box(ajson::Path::from_slice("widget.debug".as_bytes()));
-    black_box(ajson::Path::from_slice(
-        "widget.menu.#(sub_item>7)#.title".as_bytes(),
-    ));
+    black_box(ajson::Path::from_slice("widget.window.name".as_bytes()).unwrap());
+    black_box(ajson::Path::from_slice("widget.image.hOffset".as_bytes()).unwrap());
+    black_box(ajson::Path::from_slice("widget.text.onMouseUp".as_bytes()).unwrap());
+    black_box(ajson::Path::from_slice("widget.debug".as_bytes()).unwrap());
+    black_box(ajson::Path::from_slice("widget.menu.#(sub_item>7)#.title".as_bytes()).unwrap());
 }
 
 fn ajson_bench(json: &str) {
@@ -116,10 +115,12 @@ fn ajson_bench(json: &str) {
     // ajson::get(json, "widget.menu.[1.title,2.options]").as_array();
 }
 
+#[allow(dead_code)] // disabled in `criterion_benchmark`
 fn gjson_selector(json: &str) {
     black_box(gjson::get(json, "widget.[image.src,text.data]").array());
 }
 
+#[allow(dead_code)] // disabled in `criterion_benchmark`
 fn gjson_multi_query(json: &str) {
     black_box([
         gjson::get(json, "widget.image.src"),
@@ -127,6 +128,7 @@ fn gjson_multi_query(json: &str) {
     ]);
 }
 
+#[allow(dead_code)] // disabled in `criterion_benchmark`
 fn gjson_bench(json: &str) {
     black_box(gjson::get(json, "widget.window.name").str());
     black_box(gjson::get(json, "widget.image.hOffset").f64());
@@ -278,42 +280,31 @@ fn serde_json_derive_bench(json: &str) {
 }
 
 fn nom_json_bench(json: &str) {
-    black_box({
-        nom_json::root::<(&str, ErrorKind)>(json).map(|(_, value)| {
-            value["widget"]["window"]["name"].as_str();
-        });
-    });
+    if let Ok((_, value)) = nom_json::root::<(&str, ErrorKind)>(json) {
+        black_box(value["widget"]["window"]["name"].as_str());
+    }
 
-    black_box({
-        nom_json::root::<(&str, ErrorKind)>(json).map(|(_, value)| {
-            &value["widget"]["image"]["hOffset"];
-        });
-    });
+    if let Ok((_, value)) = nom_json::root::<(&str, ErrorKind)>(json) {
+        black_box(&value["widget"]["image"]["hOffset"]);
+    }
 
-    black_box({
-        nom_json::root::<(&str, ErrorKind)>(json).map(|(_, value)| {
-            value["widget"]["text"]["onMouseUp"].as_str();
-        });
-    });
+    if let Ok((_, value)) = nom_json::root::<(&str, ErrorKind)>(json) {
+        black_box(value["widget"]["text"]["onMouseUp"].as_str());
+    }
 
-    black_box({
-        nom_json::root::<(&str, ErrorKind)>(json).map(|(_, value)| {
-            value["widget"]["debug"].as_str();
-        });
-    });
+    if let Ok((_, value)) = nom_json::root::<(&str, ErrorKind)>(json) {
+        black_box(value["widget"]["debug"].as_str());
+    }
 
-    black_box({
-        nom_json::root::<(&str, ErrorKind)>(json).map(|(_, value)| {
-            let menu = &value["widget"]["menu"];
-            let v: Vec<&nom_json::JsonValue> = black_box(
-                menu.members()
-                    .filter(|x| x["sub_item"].to_f64() > 5.0)
-                    .map(|x| &x["title"])
-                    .collect(),
-            );
-            1
-        })
-    });
+    if let Ok((_, value)) = nom_json::root::<(&str, ErrorKind)>(json) {
+        let menu = &value["widget"]["menu"];
+        black_box(
+            menu.members()
+                .filter(|x| x["sub_item"].to_f64() > 5.0)
+                .map(|x| &x["title"])
+                .collect::<Vec<&nom_json::JsonValue>>(),
+        );
+    }
 }
 
 fn serde_json_derive_multi_query(json: &str) {
@@ -10,16 +10,16 @@ use nom::{
     bytes::complete::{escaped, tag, take_while},
     character::complete::{alphanumeric1 as alphanumeric, char, one_of},
     combinator::{cut, map, opt},
-    error::{context, convert_error, ErrorKind, ParseError, VerboseError},
+    error::{context, ParseError},
     multi::separated_list,
     number::complete::double,
     sequence::{delimited, preceded, separated_pair, terminated},
-    Err, IResult,
+    IResult,
 };
 
 #[derive(Debug, PartialEq)]
 pub enum JsonValue {
-    NULL,
+    Null,
     Str(String),
     Boolean(bool),
     Num(f64),
@@ -29,7 +29,7 @@ pub enum JsonValue {
 
 pub type Members<'a> = ::std::slice::Iter<'a, JsonValue>;
 
-static NULL: JsonValue = JsonValue::NULL;
+static NULL: JsonValue = JsonValue::Null;
 
 impl JsonValue {
     pub fn as_str(&self) -> &str {
@@ -46,7 +46,7 @@ impl JsonValue {
         }
     }
 
-    pub fn members(&self) -> Members {
+    pub fn members(&self) -> Members<'_> {
         match *self {
             JsonValue::Array(ref vec) => vec.iter(),
             _ => [].iter(),
@@ -54,7 +54,7 @@ impl JsonValue {
     }
 }
 
-impl<'a> Index<&'a str> for JsonValue {
+impl Index<&str> for JsonValue {
     type Output = JsonValue;
 
     fn index(&self, index: &str) -> &JsonValue {
@@ -113,11 +113,11 @@ fn boolean<'a, E: ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, bool,
 ///
 /// `context` and `cut` are related to error management:
 /// - `cut` transforms an `Err::Error(e)` in `Err::Failure(e)`, signaling to
-/// combinators like  `alt` that they should not try other parsers. We were in the
-/// right branch (since we found the `"` character) but encountered an error when
-/// parsing the string
+///   combinators like  `alt` that they should not try other parsers. We were in the
+///   right branch (since we found the `"` character) but encountered an error when
+///   parsing the string
 /// - `context` lets you add a static string to provide more information in the
-/// error chain (to indicate which parser had an error)
+///   error chain (to indicate which parser had an error)
 fn string<'a, E: ParseError<&'a str>>(i: &'a str) -> IResult<&'a str, &'a str, E> {
     context(
         "string",
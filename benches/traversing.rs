@@ -1,4 +1,3 @@
-#[macro_use]
 extern crate criterion;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
@@ -145,11 +144,12 @@ fn traversing(bytes: &[u8]) -> ajson::Result<(&[u8], &[u8])> {
         i += 1;
     }
 
-    return Ok(split_at(bytes, i));
+    Ok(split_at(bytes, i))
 
     // println!("{}", &json[..i]);
 }
 
+#[allow(dead_code)]
 fn chunk_traversing(bytes: &[u8]) -> ajson::Result<(&[u8], &[u8])> {
     let mut i = 1;
     let mut depth = 1;
@@ -211,7 +211,7 @@ fn chunk_traversing(bytes: &[u8]) -> ajson::Result<(&[u8], &[u8])> {
         i += 1;
     }
 
-    return Ok(split_at(bytes, i));
+    Ok(split_at(bytes, i))
 }
 
 pub fn traverse_benchmark(c: &mut Criterion) {
@@ -0,0 +1,282 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
+
+use crate::{
+    element::{self, Element},
+    util, Result, Value,
+};
+
+/// Options of [`diff_with`](fn.diff_with.html).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DiffOptions<'a> {
+    pub(crate) array_key: Option<&'a str>,
+}
+
+impl<'a> DiffOptions<'a> {
+    pub const fn new() -> DiffOptions<'a> {
+        DiffOptions { array_key: None }
+    }
+
+    /// Matches the objects of arrays by the value of the field instead of
+    /// their positions. The arrays are compared by positions if one of the
+    /// elements isn't an object with a unique scalar at the field.
+    pub fn array_key(mut self, field: &'a str) -> Self {
+        self.array_key = Some(field);
+        self
+    }
+}
+
+/// The kind of a [`Change`](struct.Change.html).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ChangeKind {
+    /// The value exists only in the new document.
+    Added,
+    /// The value exists only in the old document.
+    Removed,
+    /// The value differs, and has the same type in both documents.
+    Changed,
+    /// The value has another type in the new document.
+    TypeChanged,
+}
+
+/// A difference between two documents.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Change<'a> {
+    /// The path of the value, empty for the root.
+    pub path: String,
+    pub kind: ChangeKind,
+    /// The value in the old document, `None` if added.
+    pub old:  Option<Value<'a>>,
+    /// The value in the new document, `None` if removed.
+    pub new:  Option<Value<'a>>,
+}
+
+/// Compares the documents and returns the changes from `a` to `b`. The
+/// objects and arrays are walked recursively, and the numbers are compared
+/// by their values. The path of a change can be passed to
+/// [`get`](fn.get.html), the special characters in keys are escaped with
+/// `\`.
+/// ```
+/// use ajson::{ChangeKind, Result};
+/// fn main() -> Result<()> {
+///     let a = r#"{"name": "Tom", "age": 37, "fav.movie": "Deer Hunter"}"#;
+///     let b = r#"{"name": "Tom", "age": "37", "fav.movie": "Rocky", "city": "Paris"}"#;
+///
+///     let changes = ajson::diff(a, b)?;
+///     let changes: Vec<_> = changes.iter().map(|c| (c.path.as_str(), c.kind)).collect();
+///     assert_eq!(
+///         changes,
+///         vec![
+///             ("age", ChangeKind::TypeChanged),
+///             (r"fav\.movie", ChangeKind::Changed),
+///             ("city", ChangeKind::Added),
+///         ]
+///     );
+///     Ok(())
+/// }
+/// ```
+pub fn diff<'a>(a: &'a str, b: &'a str) -> Result<Vec<Change<'a>>> {
+    diff_with(a, b, DiffOptions::new())
+}
+
+/// Same as [`diff`](fn.diff.html), but with the given
+/// [`DiffOptions`](struct.DiffOptions.html).
+/// ```
+/// use ajson::{ChangeKind, DiffOptions, Result};
+/// fn main() -> Result<()> {
+///     let a = r#"[{"id": 1, "name": "Tom"}, {"id": 2, "name": "Jim"}]"#;
+///     let b = r#"[{"id": 2, "name": "Jack"}, {"id": 1, "name": "Tom"}]"#;
+///
+///     let changes = ajson::diff_with(a, b, DiffOptions::new().array_key("id"))?;
+///     assert_eq!(changes.len(), 1);
+///     assert_eq!(changes[0].path, "#(id==2).name");
+///     assert_eq!(changes[0].kind, ChangeKind::Changed);
+///     assert_eq!(changes[0].new, Some("Jack".into()));
+///     Ok(())
+/// }
+/// ```
+pub fn diff_with<'a>(a: &'a str, b: &'a str, options: DiffOptions<'a>) -> Result<Vec<Change<'a>>> {
+    let a = element::read_one(a.as_bytes())?.0;
+    let b = element::read_one(b.as_bytes())?.0;
+
+    let mut differ = Differ {
+        options,
+        changes: Vec::new(),
+    };
+    match (a, b) {
+        (Some(a), Some(b)) => differ.compare(String::new(), a, b)?,
+        (Some(a), None) => differ.push(String::new(), ChangeKind::Removed, Some(a), None),
+        (None, Some(b)) => differ.push(String::new(), ChangeKind::Added, None, Some(b)),
+        (None, None) => (),
+    }
+    Ok(differ.changes)
+}
+
+struct Differ<'a> {
+    options: DiffOptions<'a>,
+    changes: Vec<Change<'a>>,
+}
+
+impl<'a> Differ<'a> {
+    fn compare(&mut self, path: String, a: Element<'a>, b: Element<'a>) -> Result<()> {
+        if mem::discriminant(&a) != mem::discriminant(&b) {
+            self.push(path, ChangeKind::TypeChanged, Some(a), Some(b));
+            return Ok(());
+        }
+
+        match (&a, &b) {
+            (Element::Object(x), Element::Object(y)) => self.objects(&path, x, y),
+            (Element::Array(x), Element::Array(y)) => self.arrays(&path, x, y),
+            _ => {
+                if !element::equal(&a, &b)? {
+                    self.push(path, ChangeKind::Changed, Some(a), Some(b));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn objects(&mut self, path: &str, a: &'a [u8], b: &'a [u8]) -> Result<()> {
+        let a_members = element::members_by_key(a)?;
+        let b_members = element::members_by_key(b)?;
+
+        // the repeated keys are compared once, by their latter values
+        let mut seen = HashSet::new();
+        for ((key, esc), _) in element::object_members(a)? {
+            let key = element::member_key(key, esc);
+            if !seen.insert(key.clone()) {
+                continue;
+            }
+
+            let old = a_members[&key].clone();
+            let path = util::join_path(path, &util::escape_key(&key));
+            match b_members.get(&key) {
+                Some(new) => self.compare(path, old, new.clone())?,
+                None => self.push(path, ChangeKind::Removed, Some(old), None),
+            }
+        }
+
+        for ((key, esc), _) in element::object_members(b)? {
+            let key = element::member_key(key, esc);
+            if a_members.contains_key(&key) || !seen.insert(key.clone()) {
+                continue;
+            }
+
+            let new = b_members[&key].clone();
+            self.push(
+                util::join_path(path, &util::escape_key(&key)),
+                ChangeKind::Added,
+                None,
+                Some(new),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn arrays(&mut self, path: &str, a: &'a [u8], b: &'a [u8]) -> Result<()> {
+        let a = element::array_elements(a)?;
+        let b = element::array_elements(b)?;
+
+        if let Some(field) = self.options.array_key {
+            if let (Some(a_keys), Some(b_keys)) = (keys(&a, field)?, keys(&b, field)?) {
+                return self.keyed(path, field, (a, a_keys), (b, b_keys));
+            }
+        }
+
+        let n = a.len().max(b.len());
+        let mut a = a.into_iter();
+        let mut b = b.into_iter();
+        for i in 0..n {
+            let path = util::join_path(path, &i.to_string());
+            match (a.next(), b.next()) {
+                (Some(old), Some(new)) => self.compare(path, old, new)?,
+                (Some(old), None) => self.push(path, ChangeKind::Removed, Some(old), None),
+                (None, Some(new)) => self.push(path, ChangeKind::Added, None, Some(new)),
+                (None, None) => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn keyed(
+        &mut self,
+        path: &str,
+        field: &str,
+        (a, a_keys): (Vec<Element<'a>>, Keys<'a>),
+        (b, b_keys): (Vec<Element<'a>>, Keys<'a>),
+    ) -> Result<()> {
+        let a_index: HashMap<&str, usize> = a_keys
+            .iter()
+            .enumerate()
+            .map(|(i, (k, _))| (k.as_str(), i))
+            .collect();
+        let b_index: HashMap<&str, usize> = b_keys
+            .iter()
+            .enumerate()
+            .map(|(i, (k, _))| (k.as_str(), i))
+            .collect();
+
+        let query = |raw: &[u8]| {
+            let value = String::from_utf8_lossy(raw);
+            util::join_path(path, &format!("#({}=={})", util::escape_key(field), value))
+        };
+
+        for (old, (key, raw)) in a.into_iter().zip(a_keys.iter()) {
+            match b_index.get(key.as_str()) {
+                Some(&i) => self.compare(query(raw), old, b[i].clone())?,
+                None => self.push(query(raw), ChangeKind::Removed, Some(old), None),
+            }
+        }
+
+        for (new, (key, raw)) in b.iter().zip(b_keys.iter()) {
+            if !a_index.contains_key(key.as_str()) {
+                self.push(query(raw), ChangeKind::Added, None, Some(new.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push(
+        &mut self,
+        path: String,
+        kind: ChangeKind,
+        old: Option<Element<'a>>,
+        new: Option<Element<'a>>,
+    ) {
+        self.changes.push(Change {
+            path,
+            kind,
+            old: old.map(|element| element.to_value()),
+            new: new.map(|element| element.to_value()),
+        });
+    }
+}
+
+/// The values of the field of the objects in an array, in the comparable
+/// form and as they are in the document.
+type Keys<'a> = Vec<(String, &'a [u8])>;
+
+/// Reads the values of the field of the objects, returns `None` if one
+/// of the elements isn't an object with a scalar at the field, or if
+/// a value is repeated.
+fn keys<'a>(elements: &[Element<'a>], field: &str) -> Result<Option<Keys<'a>>> {
+    let mut keys = Vec::with_capacity(elements.len());
+    let mut seen = HashSet::new();
+    for element in elements {
+        let (key, raw) = match element::field_key(element, field)? {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+
+        if !seen.insert(key.clone()) {
+            return Ok(None);
+        }
+        keys.push((key, raw));
+    }
+    Ok(Some(keys))
+}
@@ -0,0 +1,606 @@
+use std::{ops::Range, str};
+
+use crate::{
+    element::{self, Element},
+    parser,
+    path::Path,
+    unescape::{escape_into, unescape},
+    util,
+    value::Value,
+    Error, Options, Result,
+};
+
+/// Sets the value at the path and returns the new JSON, the missing
+/// objects and arrays on the path are created. A key made of digits
+/// creates an array, an index past the end of an array pads it with
+/// `null`, and `-1` appends to the array.
+/// ```
+/// use ajson::Result;
+/// fn main() -> Result<()> {
+///     let json = r#"{"name": {"first": "Tom"}, "children": ["Sara"]}"#;
+///
+///     let json = ajson::set(json, "name.last", "Anderson")?;
+///     assert_eq!(
+///         json,
+///         r#"{"name": {"first": "Tom","last":"Anderson"}, "children": ["Sara"]}"#
+///     );
+///
+///     let json = ajson::set(&json, "children.2", "Jack")?;
+///     assert_eq!(
+///         ajson::get(&json, "children")?.unwrap().to_string(),
+///         r#"["Sara",null,"Jack"]"#
+///     );
+///
+///     let json = ajson::set(&json, "children.-1", "Alex")?;
+///     assert_eq!(ajson::get(&json, "children.3")?.unwrap(), "Alex");
+///
+///     let json = ajson::set("", "a.b.0", true)?;
+///     assert_eq!(json, r#"{"a":{"b":[true]}}"#);
+///     Ok(())
+/// }
+/// ```
+/// Only the keys and the indexes are supported in the path, the others
+/// such as queries, wildcards and case-insensitive keys return
+/// [`Error::Path`](enum.Error.html), as well as a JSON which has bytes
+/// before the value.
+pub fn set<'a, V>(json: &str, path: &str, value: V) -> Result<String>
+where
+    V: Into<Value<'a>>,
+{
+    set_raw(json, path, &value.into().to_json())
+}
+
+/// Same as [`set`](fn.set.html), but the value is a raw JSON which is
+/// written as it is.
+/// ```
+/// let json = ajson::set_raw(r#"{"a":1}"#, "b", r#"{"c":[1,2]}"#).unwrap();
+/// assert_eq!(json, r#"{"a":1,"b":{"c":[1,2]}}"#);
+/// ```
+pub fn set_raw(json: &str, path: &str, raw: &str) -> Result<String> {
+    let segments = plain_segments(path.as_bytes(), json)?;
+    set_segments(json, &segments, raw)
+}
+
+fn set_segments(json: &str, segments: &[Path], raw: &str) -> Result<String> {
+    match locate(json, segments)? {
+        Target::Found(span) => Ok(splice(json, span, raw)),
+        Target::Missing(span, i) => add(json, span, &segments[i..], raw),
+        Target::Scalar(span, i) => Ok(splice(json, span, &build(&segments[i..], raw))),
+    }
+}
+
+/// Inserts the value into the array before the index and returns the new
+/// JSON, `-1` or the length of the array appends to it. The missing
+/// objects and arrays on the path are created as [`set`](fn.set.html) does.
+/// ```
+/// use ajson::Result;
+/// fn main() -> Result<()> {
+///     let json = r#"{"items": [1, 2, 3]}"#;
+///     assert_eq!(
+///         ajson::insert(json, "items.1", 9)?,
+///         r#"{"items": [1, 9, 2, 3]}"#
+///     );
+///     assert_eq!(
+///         ajson::insert(json, "items.-1", 9)?,
+///         r#"{"items": [1, 2, 3, 9]}"#
+///     );
+///     Ok(())
+/// }
+/// ```
+pub fn insert<'a, V>(json: &str, path: &str, value: V) -> Result<String>
+where
+    V: Into<Value<'a>>,
+{
+    insert_raw(json, path, &value.into().to_json())
+}
+
+/// Same as [`insert`](fn.insert.html), but the value is a raw JSON which
+/// is written as it is.
+pub fn insert_raw(json: &str, path: &str, raw: &str) -> Result<String> {
+    let segments = plain_segments(path.as_bytes(), json)?;
+    let (last, parents) = segments.split_last().ok_or(Error::Path)?;
+    let index = match last.part {
+        b"-1" => None,
+        _ => Some(index(last).ok_or(Error::Path)?),
+    };
+
+    let span = match locate(json, parents)? {
+        Target::Found(span) => span,
+        _ => return set_segments(json, &segments, raw),
+    };
+
+    let bytes = json.as_bytes();
+    let array = &bytes[span.clone()];
+    if array[0] != b'[' {
+        return Err(Error::Path);
+    }
+
+    let elements = element::array_elements(array)?;
+    match index
+        .and_then(|i| elements.get(i))
+        .and_then(|element| element.raw())
+    {
+        Some(element) => Ok(insert_before(json, offset(bytes, element), raw)),
+        None => add(json, span, &segments[segments.len() - 1..], raw),
+    }
+}
+
+/// Where the value at a path is in the document.
+enum Target {
+    /// The value exists.
+    Found(Range<usize>),
+    /// The object or array misses the segment at the index.
+    Missing(Range<usize>, usize),
+    /// The value is a scalar or an empty document and the segments from
+    /// the index can't be found in it.
+    Scalar(Range<usize>, usize),
+}
+
+fn locate(json: &str, segments: &[Path]) -> Result<Target> {
+    let bytes = json.as_bytes();
+
+    // the span of the current value in the document
+    let (mut start, mut end) = match util::trim_whitespace_u8(bytes) {
+        [] => return Ok(Target::Scalar(0..bytes.len(), 0)),
+        // `get` skips the bytes before the value, but they can't be replaced
+        [b, ..] if !element::starts_value(*b) => return Err(Error::Path),
+        v => {
+            let start = offset(bytes, v);
+            (start, start + v.len())
+        }
+    };
+
+    for (i, segment) in segments.iter().enumerate() {
+        let current = &bytes[start..end];
+        match current[0] {
+            b'[' if segment.part == b"-1" => return Ok(Target::Missing(start..end, i)),
+            b'{' | b'[' => (),
+            _ => return Ok(Target::Scalar(start..end, i)),
+        }
+
+        let found = parser::bytes_get(current, segment)?.0;
+        match found.as_ref().and_then(|element| element.raw()) {
+            Some(v) => {
+                start = offset(bytes, v);
+                end = start + v.len();
+            }
+            None => return Ok(Target::Missing(start..end, i)),
+        }
+    }
+
+    Ok(Target::Found(start..end))
+}
+
+/// Deletes the values at the path and returns the new JSON, the commas
+/// around the deleted members and elements are removed as well. The
+/// wildcards and the `#(...)#` queries delete all the matched values, the
+/// JSON is returned unchanged if nothing matches.
+/// ```
+/// use ajson::Result;
+/// fn main() -> Result<()> {
+///     let json = r#"{"name": "Tom", "age": 37, "friends": [{"age": 44}, {"age": 68}]}"#;
+///
+///     assert_eq!(
+///         ajson::delete(json, "age")?,
+///         r#"{"name": "Tom", "friends": [{"age": 44}, {"age": 68}]}"#
+///     );
+///     assert_eq!(
+///         ajson::delete(json, "friends.#(age>50)#")?,
+///         r#"{"name": "Tom", "age": 37, "friends": [{"age": 44}]}"#
+///     );
+///     assert_eq!(
+///         ajson::delete(json, "friends.#.age")?,
+///         r#"{"name": "Tom", "age": 37, "friends": [{}, {}]}"#
+///     );
+///     Ok(())
+/// }
+/// ```
+pub fn delete(json: &str, path: &str) -> Result<String> {
+    let mut ranges = Vec::new();
+    walk(json, path, &mut |children, matched| {
+        push_removals(children, matched, &mut ranges)
+    })?;
+    Ok(remove_ranges(json, ranges))
+}
+
+/// Finds the values at the path, which may have wildcards and queries,
+/// and calls `f` with the members or elements of each parent and the
+/// indexes of the matched ones. A trailing `#` without a query returns
+/// [`Error::Path`](../enum.Error.html).
+pub(crate) fn walk<'a>(
+    json: &'a str,
+    path: &'a str,
+    f: &mut dyn FnMut(&[Child<'a>], &[usize]),
+) -> Result<()> {
+    let segments = segments(path.as_bytes(), json)?;
+    let last = &segments[segments.len() - 1];
+    if last.arrch && !last.borrow_query().on {
+        return Err(Error::Path);
+    }
+
+    let bytes = json.as_bytes();
+    match util::trim_whitespace_u8(bytes) {
+        [] => Ok(()),
+        root => walk_segments(bytes, root, &segments, f),
+    }
+}
+
+/// A member of an object or an element of an array, and its span in the
+/// document, the key included.
+pub(crate) struct Child<'a> {
+    pub(crate) key:   Option<(&'a [u8], bool)>,
+    pub(crate) value: Element<'a>,
+    pub(crate) span:  Range<usize>,
+}
+
+impl<'a> Child<'a> {
+    /// Returns true if the key of the member equals the unescaped key.
+    pub(crate) fn has_key(&self, key: &str) -> bool {
+        match self.key {
+            Some((k, true)) => unescape(k) == key,
+            Some((k, false)) => k == key.as_bytes(),
+            None => false,
+        }
+    }
+
+    /// Returns the span of the value, without the key.
+    pub(crate) fn value_span(&self) -> Range<usize> {
+        let len = self.value.raw().map_or(0, |raw| raw.len());
+        self.span.end - len..self.span.end
+    }
+}
+
+/// Reads the members of the object or the elements of the array, the
+/// keys are unquoted.
+pub(crate) fn children<'a>(bytes: &'a [u8], container: &'a [u8]) -> Result<Vec<Child<'a>>> {
+    let children = match container[0] {
+        b'{' => element::object_members(container)?
+            .into_iter()
+            .filter_map(|((key, esc), value)| {
+                let raw = value.raw()?;
+                let span = offset(bytes, key)..offset(bytes, raw) + raw.len();
+                let key = Some((&key[1..key.len() - 1], esc));
+                Some(Child { key, value, span })
+            })
+            .collect(),
+        b'[' => element::array_elements(container)?
+            .into_iter()
+            .filter_map(|value| {
+                let raw = value.raw()?;
+                let span = offset(bytes, raw)..offset(bytes, raw) + raw.len();
+                Some(Child {
+                    key: None,
+                    value,
+                    span,
+                })
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    Ok(children)
+}
+
+/// Removes the children at the indexes from the object or array at the
+/// span, the indexes must be sorted.
+pub(crate) fn remove_children(json: &str, span: Range<usize>, indexes: &[usize]) -> Result<String> {
+    let bytes = json.as_bytes();
+    let children = children(bytes, &bytes[span])?;
+    let mut ranges = Vec::new();
+    push_removals(&children, indexes, &mut ranges);
+    Ok(remove_ranges(json, ranges))
+}
+
+/// Replaces the spans with the strings, the spans inside an earlier one
+/// and the repeated ones are skipped.
+pub(crate) fn replace_ranges(json: &str, mut replaced: Vec<(Range<usize>, String)>) -> String {
+    replaced.sort_unstable_by_key(|(span, _)| (span.start, usize::MAX - span.end));
+
+    let mut s = String::with_capacity(json.len());
+    let mut last = 0;
+    for (span, text) in replaced {
+        if span.start < last {
+            continue;
+        }
+        s.push_str(&json[last..span.start]);
+        s.push_str(&text);
+        last = span.end;
+    }
+    s.push_str(&json[last..]);
+    s
+}
+
+pub(crate) fn remove_ranges(json: &str, mut ranges: Vec<Range<usize>>) -> String {
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let mut s = String::with_capacity(json.len());
+    let mut last = 0;
+    for range in ranges {
+        s.push_str(&json[last..range.start]);
+        last = range.end;
+    }
+    s.push_str(&json[last..]);
+    s
+}
+
+fn walk_segments<'a>(
+    bytes: &'a [u8],
+    container: &'a [u8],
+    segments: &[Path<'a>],
+    f: &mut dyn FnMut(&[Child<'a>], &[usize]),
+) -> Result<()> {
+    let children = children(bytes, container)?;
+    if children.is_empty() {
+        return Ok(());
+    }
+
+    let matched = matched(&segments[0], container[0] == b'{', &children)?;
+    if segments.len() == 1 {
+        f(&children, &matched);
+        return Ok(());
+    }
+
+    for i in matched {
+        if let Some(raw) = children[i].value.raw() {
+            walk_segments(bytes, raw, &segments[1..], f)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the indexes of the children matched by the segment.
+fn matched(segment: &Path, object: bool, children: &[Child]) -> Result<Vec<usize>> {
+    let query = segment.borrow_query();
+    if segment.arrch && query.on {
+        let mut matched = Vec::new();
+        for (i, child) in children.iter().enumerate() {
+            if query.match_element(&child.value)? {
+                matched.push(i);
+                if !query.all {
+                    break;
+                }
+            }
+        }
+        return Ok(matched);
+    }
+
+    if segment.arrch {
+        return match object {
+            true => Ok(vec![]),
+            false => Ok((0..children.len()).collect()),
+        };
+    }
+
+    if object {
+        let matched = children
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| {
+                child
+                    .key
+                    .is_some_and(|(key, esc)| segment.is_match(key, esc))
+            })
+            .map(|(i, _)| i);
+        return Ok(matched.collect());
+    }
+
+    let len = children.len() as i64;
+    let index = match str::from_utf8(segment.part)
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+    {
+        Some(index) if index < 0 => index + len,
+        Some(index) => index,
+        None => return Ok(vec![]),
+    };
+    match (0..len).contains(&index) {
+        true => Ok(vec![index as usize]),
+        false => Ok(vec![]),
+    }
+}
+
+/// Pushes the spans of the removed children, each one takes the comma
+/// after it, and the trailing ones take the comma before them.
+pub(crate) fn push_removals(children: &[Child], matched: &[usize], ranges: &mut Vec<Range<usize>>) {
+    if matched.is_empty() {
+        return;
+    }
+
+    let last = children.len() - 1;
+    let last_kept = (0..children.len())
+        .rev()
+        .find(|i| matched.binary_search(i).is_err());
+
+    match last_kept {
+        Some(kept) => {
+            for &i in matched.iter().filter(|&&i| i < kept) {
+                ranges.push(children[i].span.start..children[i + 1].span.start);
+            }
+            if kept < last {
+                ranges.push(children[kept].span.end..children[last].span.end);
+            }
+        }
+        None => ranges.push(children[0].span.start..children[last].span.end),
+    }
+}
+
+/// Adds the missing member or element to the object or array at the span.
+fn add(json: &str, span: Range<usize>, segments: &[Path], raw: &str) -> Result<String> {
+    let bytes = json.as_bytes();
+    let container = &bytes[span.clone()];
+    let value = build(&segments[1..], raw);
+
+    let added = match container[0] {
+        b'{' => {
+            let mut member = String::new();
+            write_key(&segments[0], &mut member);
+            member.push(':');
+            member.push_str(&value);
+            vec![member]
+        }
+        _ => {
+            let len = element::array_elements(container)?.len();
+            let index = match segments[0].part {
+                b"-1" => len,
+                _ => index(&segments[0]).ok_or(Error::Path)?,
+            };
+            let mut added = vec!["null".to_string(); index.saturating_sub(len)];
+            added.push(value);
+            added
+        }
+    };
+
+    append(json, span, &added)
+}
+
+/// Appends the raw members or elements to the object or array at the span,
+/// the whitespace before the last one is repeated for them.
+pub(crate) fn append(json: &str, span: Range<usize>, added: &[String]) -> Result<String> {
+    let bytes = json.as_bytes();
+    let container = &bytes[span.clone()];
+
+    // the reader accepts the unclosed containers
+    let (closing, error) = match container[0] {
+        b'{' => (b'}', Error::Object),
+        _ => (b']', Error::Array),
+    };
+    if container.len() < 2 || container[container.len() - 1] != closing {
+        return Err(error);
+    }
+
+    let last = match container[0] {
+        b'{' => element::object_members(container)?
+            .last()
+            .map(|(key, _)| key.0),
+        _ => element::array_elements(container)?
+            .last()
+            .and_then(|element| element.raw()),
+    };
+
+    match last {
+        Some(last) => {
+            let space = whitespace_before(bytes, offset(bytes, last));
+            let added = added.join(&format!(",{}", space));
+            let inner = util::trim_whitespace_u8(&container[1..container.len() - 1]);
+            let at = offset(bytes, inner) + inner.len();
+            Ok(splice(json, at..at, &format!(",{}{}", space, added)))
+        }
+        None => {
+            let at = span.start + 1;
+            Ok(splice(json, at..at, &added.join(",")))
+        }
+    }
+}
+
+/// Inserts the raw element before the one at the offset, the whitespace
+/// before it is repeated after the comma.
+pub(crate) fn insert_before(json: &str, at: usize, raw: &str) -> String {
+    let inserted = format!("{},{}", raw, whitespace_before(json.as_bytes(), at));
+    splice(json, at..at, &inserted)
+}
+
+/// Returns the whitespace right before the offset.
+fn whitespace_before(bytes: &[u8], at: usize) -> &str {
+    let n = bytes[..at]
+        .iter()
+        .rev()
+        .take_while(|b| b.is_ascii_whitespace())
+        .count();
+    unsafe { str::from_utf8_unchecked(&bytes[at - n..at]) }
+}
+
+/// Builds the value nested in the missing containers of the path.
+fn build(segments: &[Path], raw: &str) -> String {
+    let mut json = raw.to_string();
+    for segment in segments.iter().rev() {
+        let index = match segment.part {
+            b"-1" => Some(0),
+            _ => index(segment),
+        };
+        json = match index {
+            Some(index) => {
+                let mut elements = vec!["null"; index];
+                elements.push(&json);
+                format!("[{}]", elements.join(","))
+            }
+            None => {
+                let mut object = String::from("{");
+                write_key(segment, &mut object);
+                object.push(':');
+                object.push_str(&json);
+                object.push('}');
+                object
+            }
+        };
+    }
+    json
+}
+
+/// Splits the path into the segments, each one is a key, an index or a
+/// query.
+fn segments<'a>(v: &'a [u8], json: &'a str) -> Result<Vec<Path<'a>>> {
+    let mut segments = Vec::new();
+    let opts = Options::new().root(json.as_bytes());
+    let mut path = Path::from_slice_with(v, opts)?;
+
+    while path.ok {
+        let supported = !path.part.is_empty()
+            && !path.descent
+            && !path.lines
+            && !path.has_selectors()
+            && !path.has_modifier()
+            && path.literal.is_none()
+            && path.slice.is_none();
+        if !supported {
+            return Err(Error::Path);
+        }
+
+        let next = path.parse_next()?;
+        path.more = false;
+        path.next = &[];
+        segments.push(path);
+        path = next;
+    }
+
+    match segments.is_empty() {
+        true => Err(Error::Path),
+        false => Ok(segments),
+    }
+}
+
+/// Same as `segments`, but only the keys and the indexes are allowed, the
+/// case-insensitive keys such as `a~` can't name the key to write.
+fn plain_segments<'a>(v: &'a [u8], json: &'a str) -> Result<Vec<Path<'a>>> {
+    let segments = segments(v, json)?;
+    let special = |segment: &Path| segment.arrch || segment.wild || segment.fold;
+    match segments.iter().any(special) {
+        true => Err(Error::Path),
+        false => Ok(segments),
+    }
+}
+
+/// Returns the index if the key of the segment is made of digits.
+fn index(segment: &Path) -> Option<usize> {
+    match segment.part.iter().all(u8::is_ascii_digit) {
+        true => str::from_utf8(segment.part).ok()?.parse().ok(),
+        false => None,
+    }
+}
+
+fn write_key(segment: &Path, buffer: &mut String) {
+    let key = util::strip_escapes(segment.part);
+    escape_into(&String::from_utf8_lossy(&key), buffer);
+}
+
+/// Returns the offset of the sub slice in the slice.
+pub(crate) fn offset(bytes: &[u8], sub: &[u8]) -> usize {
+    sub.as_ptr() as usize - bytes.as_ptr() as usize
+}
+
+pub(crate) fn splice(json: &str, span: Range<usize>, raw: &str) -> String {
+    let mut s = String::with_capacity(json.len() - span.len() + raw.len());
+    s.push_str(&json[..span.start]);
+    s.push_str(raw);
+    s.push_str(&json[span.end..]);
+    s
+}
@@ -1,6 +1,10 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, iter::FromIterator};
 
-use crate::{unescape, value::Value, Number, Result};
+use crate::{
+    unescape::{escape_into, unescape},
+    value::Value,
+    Number, Result,
+};
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum Element<'a> {
@@ -12,7 +16,10 @@ pub enum Element<'a> {
     Number(&'a [u8]),
     Count(usize),
     List(Vec<Element<'a>>),
-    Map(HashMap<(&'a [u8], bool), Element<'a>>),
+    /// The members of a constructed object, in the order of insertion.
+    Map(Vec<Member<'a>>),
+    /// The value returned by a user-defined modifier.
+    Value(Value<'a>),
 }
 
 impl<'a> Element<'a> {
@@ -44,62 +51,49 @@ impl<'a> Element<'a> {
                 Value::Number(n)
             }
             Element::Count(num) => Value::Usize(*num),
-            Element::List(elements) => {
+            Element::List(_) => {
                 let mut array_string = String::new();
-                array_string.push('[');
-                let size = elements.len();
-
-                for (i, element) in elements.iter().enumerate() {
-                    element.write_to_string_buffer(&mut array_string);
-                    if i < size - 1 {
-                        array_string.push(',');
-                    }
-                }
-
-                array_string.push(']');
+                self.write_to_string_buffer(&mut array_string);
                 Value::Array(Cow::Owned(array_string))
             }
-            Element::Map(elements) => {
+            Element::Map(_) => {
                 let mut object_string = String::new();
-                object_string.push('{');
-                let size = elements.len();
-                let mut count = 0;
-
-                for ((buf, esc), element) in elements {
-                    count += 1;
-
-                    if *esc {
-                        let s = unescape(&buf[1..buf.len() - 1]);
-                        object_string.push_str(s.as_str());
-                    } else {
-                        let s = unsafe { std::str::from_utf8_unchecked(buf) };
-                        object_string.push_str(s);
-                    };
-
-                    object_string.push(':');
-                    element.write_to_string_buffer(&mut object_string);
-                    if count < size {
-                        object_string.push(',');
-                    }
-                }
-
-                object_string.push('}');
+                self.write_to_string_buffer(&mut object_string);
                 Value::Object(Cow::Owned(object_string))
             }
             Element::Null(_) => Value::Null,
+            Element::Value(v) => v.clone(),
         }
     }
 
+    /// Returns the raw JSON of the element if it is a part of the input.
+    pub fn raw(&self) -> Option<&'a [u8]> {
+        match *self {
+            Element::String(s, _)
+            | Element::Object(s)
+            | Element::Array(s)
+            | Element::Null(s)
+            | Element::Boolean(s)
+            | Element::Number(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Same as `raw`, but as a `str`, empty if it isn't a part of the input.
+    pub(crate) fn raw_str(&self) -> &'a str {
+        text(self.raw().unwrap_or_default())
+    }
+
+    /// Writes the element as JSON, the strings are escaped again so that
+    /// the output is always valid.
     fn write_to_string_buffer(&self, buffer: &mut String) {
         match *self {
             Element::String(buf, esc) => {
-                if esc {
-                    let s = unescape(&buf[1..buf.len() - 1]);
-                    buffer.push_str(s.as_str());
-                } else {
-                    let s = unsafe { std::str::from_utf8_unchecked(buf) };
-                    buffer.push_str(s);
-                };
+                let s = &buf[1..buf.len() - 1];
+                match esc {
+                    true => escape_into(&unescape(s), buffer),
+                    false => escape_into(unsafe { std::str::from_utf8_unchecked(s) }, buffer),
+                }
             }
 
             Element::Object(s)
@@ -110,20 +104,36 @@ impl<'a> Element<'a> {
                 let s = unsafe { std::str::from_utf8_unchecked(s) };
                 buffer.push_str(s);
             }
+            Element::Count(num) => buffer.push_str(&num.to_string()),
             Element::List(ref elements) => {
                 buffer.push('[');
-                let size = elements.len();
-
                 for (i, element) in elements.iter().enumerate() {
-                    element.write_to_string_buffer(buffer);
-                    if i < size - 1 {
+                    if i > 0 {
                         buffer.push(',');
                     }
+                    element.write_to_string_buffer(buffer);
                 }
-
                 buffer.push(']');
             }
-            _ => (),
+            Element::Map(ref members) => {
+                buffer.push('{');
+                for (i, ((key, esc), element)) in members.iter().enumerate() {
+                    if i > 0 {
+                        buffer.push(',');
+                    }
+                    match esc {
+                        true => escape_into(&unescape(key), buffer),
+                        false => escape_into(&String::from_utf8_lossy(key), buffer),
+                    }
+                    buffer.push(':');
+                    element.write_to_string_buffer(buffer);
+                }
+                buffer.push('}');
+            }
+            Element::Value(ref v) => match v {
+                Value::String(s) => escape_into(s, buffer),
+                v => buffer.push_str(&v.to_string()),
+            },
         }
     }
 }
@@ -275,7 +285,7 @@ pub fn compound_u8(bytes: &[u8]) -> Result<(&[u8], &[u8])> {
         i -= bytes.len();
     }
 
-    return Ok(split_at_u8(bytes, i));
+    Ok(split_at_u8(bytes, i))
 }
 
 #[cfg(test)]
@@ -302,7 +312,7 @@ pub fn number_u8(bytes: &[u8]) -> Result<(&[u8], &[u8])> {
 
         match b {
             b'0'..=b'9' => (),
-            b'-' | b'.' => (),
+            b'-' | b'.' | b'e' | b'E' | b'+' => (),
             _ => break,
         }
         i += 1;
@@ -311,39 +321,285 @@ pub fn number_u8(bytes: &[u8]) -> Result<(&[u8], &[u8])> {
     Ok(split_at_u8(bytes, i))
 }
 
+/// Iterates the elements of a JSON array, starts after the `[`.
+pub struct ArrayIter<'a> {
+    pub bytes: &'a [u8],
+}
+
+impl<'a> ArrayIter<'a> {
+    pub fn new(bytes: &'a [u8]) -> ArrayIter<'a> {
+        ArrayIter { bytes }
+    }
+}
+
+impl<'a> Iterator for ArrayIter<'a> {
+    type Item = Result<Element<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match read_one(self.bytes) {
+            Ok((element, left)) => {
+                self.bytes = left;
+                element.map(Ok)
+            }
+            Err(e) => {
+                self.bytes = &[];
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads the elements of a JSON array, the first byte must be `[`.
+pub fn array_elements(bytes: &[u8]) -> Result<Vec<Element<'_>>> {
+    ArrayIter::new(&bytes[1..]).collect()
+}
+
+/// A member of JSON object, the key and whether it is escaped.
+pub type Member<'a> = ((&'a [u8], bool), Element<'a>);
+
+/// Collects the members of a constructed object, keeping the order of
+/// insertion. A repeated key replaces the value of the former member.
+#[derive(Default)]
+pub struct MapBuilder<'a> {
+    members: Vec<Member<'a>>,
+    index:   HashMap<Cow<'a, [u8]>, usize>,
+}
+
+impl<'a> MapBuilder<'a> {
+    pub fn new() -> MapBuilder<'a> {
+        Default::default()
+    }
+
+    /// Inserts a member, the key must be unquoted.
+    pub fn insert(&mut self, key: (&'a [u8], bool), value: Element<'a>) {
+        let name = match key.1 {
+            true => Cow::Owned(unescape(key.0).into_bytes()),
+            false => Cow::Borrowed(key.0),
+        };
+
+        match self.index.get(&name) {
+            Some(&i) => self.members[i].1 = value,
+            None => {
+                self.index.insert(name, self.members.len());
+                self.members.push((key, value));
+            }
+        }
+    }
+
+    pub fn build(self) -> Element<'a> {
+        Element::Map(self.members)
+    }
+}
+
+impl<'a> FromIterator<Member<'a>> for MapBuilder<'a> {
+    fn from_iter<I: IntoIterator<Item = Member<'a>>>(iter: I) -> Self {
+        let mut builder = MapBuilder::new();
+        for (key, value) in iter {
+            builder.insert(key, value);
+        }
+        builder
+    }
+}
+
+/// Compares two JSON values, the numbers are compared by their values and
+/// the members of objects regardless of the order.
+pub fn equal(a: &Element, b: &Element) -> Result<bool> {
+    let equal = match (a, b) {
+        (Element::String(..), Element::String(..)) => a.to_value() == b.to_value(),
+        (Element::Number(a), Element::Number(b)) => number_equal(a, b),
+        (Element::Boolean(a), Element::Boolean(b)) => a == b,
+        (Element::Null(_), Element::Null(_)) => true,
+        (Element::Array(a), Element::Array(b)) => {
+            let (a, b) = (array_elements(a)?, array_elements(b)?);
+            if a.len() != b.len() {
+                return Ok(false);
+            }
+            for (a, b) in a.iter().zip(b.iter()) {
+                if !equal(a, b)? {
+                    return Ok(false);
+                }
+            }
+            true
+        }
+        (Element::Object(a), Element::Object(b)) => {
+            let b = members_by_key(b)?;
+            let a = members_by_key(a)?;
+            if a.len() != b.len() {
+                return Ok(false);
+            }
+            for (key, a) in a.iter() {
+                match b.get(key) {
+                    Some(b) if equal(a, b)? => (),
+                    _ => return Ok(false),
+                }
+            }
+            true
+        }
+        _ => false,
+    };
+
+    Ok(equal)
+}
+
+fn number_equal(a: &[u8], b: &[u8]) -> bool {
+    let (a, b) = (Number::from(a), Number::from(b));
+    match (&a, &b) {
+        (Number::F64(_), _) | (_, Number::F64(_)) => a.to_f64() == b.to_f64(),
+        _ => match (a.as_str().parse::<i128>(), b.as_str().parse::<i128>()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => a.to_f64() == b.to_f64(),
+        },
+    }
+}
+
+/// Reads the members of the object by the unescaped keys, the latter
+/// member wins if a key is repeated.
+pub(crate) fn members_by_key(bytes: &[u8]) -> Result<HashMap<Cow<'_, str>, Element<'_>>> {
+    let mut members = HashMap::new();
+    for ((key, esc), value) in object_members(bytes)? {
+        members.insert(member_key(key, esc), value);
+    }
+    Ok(members)
+}
+
+/// Reads the scalar at the field of the object, returns it in a form which
+/// is the same for the equal values, and as it is in the document. `None`
+/// is returned if the element isn't an object or the value isn't a scalar.
+pub(crate) fn field_key<'a>(
+    element: &Element<'a>,
+    field: &str,
+) -> Result<Option<(String, &'a [u8])>> {
+    let members = match element {
+        Element::Object(s) => members_by_key(s)?,
+        _ => return Ok(None),
+    };
+
+    let value = match members.get(field) {
+        Some(value @ Element::String(..))
+        | Some(value @ Element::Number(_))
+        | Some(value @ Element::Boolean(_))
+        | Some(value @ Element::Null(_)) => value,
+        _ => return Ok(None),
+    };
+    let key = match value.to_value() {
+        Value::Number(n) => n.to_f64().to_string(),
+        v => v.to_json().into_owned(),
+    };
+    Ok(Some((key, value.raw().unwrap_or_default())))
+}
+
+/// Unquotes and unescapes the key of a member.
+pub(crate) fn member_key(key: &[u8], esc: bool) -> Cow<'_, str> {
+    let key = &key[1..key.len() - 1];
+    match esc {
+        true => Cow::Owned(unescape(key)),
+        false => String::from_utf8_lossy(key),
+    }
+}
+
+#[cfg(test)]
+mod test_map_builder {
+    use super::{Element, MapBuilder};
+
+    #[test]
+    fn test_insert() {
+        let mut map = MapBuilder::new();
+        map.insert((b"b", false), Element::Number(b"1"));
+        map.insert((b"a", false), Element::Number(b"2"));
+        map.insert((br"\u0062", true), Element::Number(b"3"));
+
+        assert_eq!(
+            map.build(),
+            Element::Map(vec![
+                ((b"b", false), Element::Number(b"3")),
+                ((b"a", false), Element::Number(b"2")),
+            ])
+        );
+    }
+}
+
+/// Reads the members of a JSON object, the first byte must be `{`.
+/// The keys are returned with quotes.
+pub fn object_members(bytes: &[u8]) -> Result<Vec<Member<'_>>> {
+    let mut members = Vec::new();
+    let mut input = bytes;
+    let mut i = 1;
+
+    while i < input.len() {
+        let b = input[i];
+        if b == b'}' {
+            break;
+        }
+
+        if b != b'"' {
+            i += 1;
+            continue;
+        }
+
+        let (key, left, esc) = string_u8(&input[i..])?;
+        if key.len() < 2 {
+            break;
+        }
+
+        let (element, left) = read_one(left)?;
+        match element {
+            Some(element) => members.push(((key, esc), element)),
+            None => break,
+        }
+        input = left;
+        i = 0;
+    }
+
+    Ok(members)
+}
+
+/// Returns true if the byte is the first one of a JSON value, `read_one`
+/// skips the others.
+pub(crate) fn starts_value(b: u8) -> bool {
+    matches!(
+        b,
+        b'"' | b'{' | b'[' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n'
+    )
+}
+
+// the bytes are read from a `&str`
+pub(crate) fn text(bytes: &[u8]) -> &str {
+    unsafe { std::str::from_utf8_unchecked(bytes) }
+}
+
 pub type MakeResult<'a> = Result<(Option<Element<'a>>, &'a [u8])>;
 
 pub type MakeFn = fn(&[u8]) -> MakeResult;
 
-fn make_string(input: &[u8]) -> MakeResult {
+fn make_string(input: &[u8]) -> MakeResult<'_> {
     string_u8(input).map(|(a, b, esc)| (Some(Element::String(a, esc)), b))
 }
 
-fn make_true(input: &[u8]) -> MakeResult {
+fn make_true(input: &[u8]) -> MakeResult<'_> {
     true_u8(input).map(|(a, b)| (Some(Element::Boolean(a)), b))
 }
 
-fn make_false(input: &[u8]) -> MakeResult {
+fn make_false(input: &[u8]) -> MakeResult<'_> {
     false_u8(input).map(|(a, b)| (Some(Element::Boolean(a)), b))
 }
 
-fn make_null(input: &[u8]) -> MakeResult {
+fn make_null(input: &[u8]) -> MakeResult<'_> {
     null_u8(input).map(|(a, b)| (Some(Element::Null(a)), b))
 }
 
-fn make_number(input: &[u8]) -> MakeResult {
+fn make_number(input: &[u8]) -> MakeResult<'_> {
     number_u8(input).map(|(a, b)| (Some(Element::Number(a)), b))
 }
 
-fn make_array(input: &[u8]) -> MakeResult {
+fn make_array(input: &[u8]) -> MakeResult<'_> {
     compound_u8(input).map(|(a, b)| (Some(Element::Array(a)), b))
 }
 
-fn make_object(input: &[u8]) -> MakeResult {
+fn make_object(input: &[u8]) -> MakeResult<'_> {
     compound_u8(input).map(|(a, b)| (Some(Element::Object(a)), b))
 }
 
-pub fn read_one(input: &[u8]) -> Result<(Option<Element>, &[u8])> {
+pub fn read_one(input: &[u8]) -> Result<(Option<Element<'_>>, &[u8])> {
     let mut i = 0;
 
     const MAKER: [Option<MakeFn>; 256] = {
@@ -41,7 +41,7 @@ JSON example
 #### basic
 Below is a quick overview of the path syntax, for more complete information please check out GJSON Syntax.
 
-A path is a series of keys separated by a dot. A key may contain special wildcard characters '*' and '?'. To access an array value use the index as the key. To get the number of elements in an array or to access a child path, use the '#' character. The dot and wildcard characters can be escaped with ''.
+A path is a series of keys separated by a dot. A key may contain special wildcard characters '*' and '?', '*' matches any number of characters and '?' matches exactly one. To access an array value use the index as the key. To get the number of elements in an array or to access a child path, use the '#' character. The dot and wildcard characters can be escaped with '\'.
 
 ```text
 name.last        >> "Anderson"
@@ -57,7 +57,7 @@ friends.1.last   >> "Craig"
 ```
 
 #### Escape character
-Special purpose characters, such as ., *, and ? can be escaped with .
+Special purpose characters, such as ., *, and ? can be escaped with \.
 
 ```text
 fav\.movie             "Deer Hunter"
@@ -70,6 +70,16 @@ friends.#              3
 friends.#.age         [44,68,47]
 ```
 
+A negative index counts from the end of the array, and `[start:end:step]` takes a slice of it, the bounds can be omitted or negative.
+```text
+children.-1           "Jack"
+friends.-1.first      "Jane"
+children.[1:]         ["Alex","Jack"]
+children.[::-1]       ["Jack","Alex","Sara"]
+friends.[:2].first    ["Dale","Roger"]
+friends.[::2]|#       2
+```
+
 #### queries
 You can also query an array for the first match by using #(...), or find all matches with #(...)#. Queries support the ==, !=, <, <=, >, >= comparison operators and the simple pattern matching % (like) and !% (not like) operators.
 
@@ -81,34 +91,173 @@ friends.#(first%"D*").last        >> "Murphy"
 friends.#(nets.#(=="fb"))#.first  >> ["Dale","Roger"]
 ```
 
+Queries also work on objects, the condition is tested against the value of each member. `#(...)` returns the first matched value, and `#(...)#` returns an object with the matched members.
+
+```text
+{"users": {"u1": {"name": "Dale", "age": 44}, "u2": {"name": "Roger", "age": 68}}}
+```
+
+```text
+users.#(age>40).name       >> "Dale"
+users.#(age>60)#           >> {"u2":{"name": "Roger", "age": 68}}
+users.#(age>40)#.name      >> {"u1":"Dale","u2":"Roger"}
+users.#(age>40)#|@keys     >> ["u1","u2"]
+```
+
+With the `regex` feature, `=~` and `!~` match a string against a regular expression, written as `/pattern/flags` or as a string. The supported flags are `i` (case-insensitive), `m` (multi-line), `s` (`.` matches `\n`) and `x` (ignore whitespace). Without the feature, a path using `=~` or `!~` returns `Error::Path`.
+
+```text
+friends.#(first=~/^[DJ]a/)#.first   >> ["Dale","Jane"]
+friends.#(first=~/^r/i).last        >> "Craig"
+friends.#(last!~"^Mur")#.first      >> ["Roger"]
+```
+
+The `in` and `!in` operators test whether a value is one of the values in a JSON array, strings only match strings, numbers only match numbers and so on.
+
+```text
+friends.#(last in ["Murphy","Craig"])#.first   >> ["Dale","Roger","Jane"]
+friends.#(age !in [44,47])#.first              >> ["Roger"]
+children.#(in ["Sara","Jack"])#                >> ["Sara","Jack"]
+```
+
+The right side of a comparison can also be a path, `@.path` is relative to the element being tested and `$.path` to the root of the document.
+
+```text
+{
+  "currentTeam": "blue",
+  "items": [
+    {"name": "a", "price": 10, "cost": 12, "team": "red"},
+    {"name": "b", "price": 30, "cost": 20, "team": "blue"}
+  ]
+}
+```
+
+```text
+items.#(price>@.cost)#.name          >> ["b"]
+items.#(team==$.currentTeam).name    >> "b"
+```
+
+Comparisons can be combined with `&&`, `||`, `!` and parentheses, `&&` binds tighter than `||`.
+
+```text
+friends.#(age>45 && last=="Murphy").first                    >> "Jane"
+friends.#(age<45 || age>60)#.first                           >> ["Dale","Roger"]
+friends.#(!(last=="Murphy"))#.first                          >> ["Roger"]
+friends.#(age>40 && (last=="Murphy" || first%"R*"))#.first   >> ["Dale","Roger","Jane"]
+```
+
+#### case-insensitive
+A `~` at the end of a key matches it case-insensitively, so a key which ends with `~` must escape it, e.g. `a\~`. The `~=`, `!~=`, `~%` and `!~%` operators are the case-insensitive forms of `==`, `!=`, `%` and `!%`. The case folding follows Unicode, so `"STRASSE"` equals `"straße"`.
+
+```text
+NAME~.First~                         >> "Tom"
+friends.#(first~="dale").last        >> "Murphy"
+friends.#(last~%"mur*")#.first       >> ["Dale","Jane"]
+```
+
+To match all the keys and comparisons of a path case-insensitively, use [`Options::case_insensitive`](struct.Options.html#method.case_insensitive) with `get_with`.
+
+#### recursive descent
+The `**` walks the current value and all the values nested in it. Like `#`, the path after `**.` is applied to each of them, so it finds a key at any depth, and the path after `|` is applied to the whole result.
+
+```text
+**.first                         >> ["Tom","Dale","Roger","Jane"]
+friends.**.nets                  >> [["ig","fb","tw"],["fb","tw"],["ig","tw"]]
+**.nets|@flatten|#               >> 7
+**|#(last=="Murphy")#.first      >> ["Dale","Jane"]
+```
+
+#### dot vs pipe
+The `.` is the standard separator, but it's also possible to use a `|`. In most cases they both end up returning the same results. The cases where `|` differs from `.` is when it's used after the `#` for arrays and queries: the path after `#.` is applied to each element, while the path after `|` is applied to the whole result.
+
+```text
+friends.#(last=="Murphy")#.first   >> ["Dale","Jane"]
+friends.#(last=="Murphy")#|first   >> not exists
+friends.#(last=="Murphy")#|#       >> 2
+friends.#.first|1                  >> "Roger"
+children|0                         >> "Sara"
+```
+
+#### JSON Lines
+A path that starts with `..` treats the input as newline delimited JSON, each line is an element of an array.
+
+```text
+{"name": "Gilbert", "age": 61}
+{"name": "Alexa", "age": 34}
+{"name": "May", "age": 57}
+```
+
+```text
+..#                      >> 3
+..1                      >> {"name": "Alexa", "age": 34}
+..#.name                 >> ["Gilbert","Alexa","May"]
+..#(name="May").age      >> 57
+..#(age>40)#|#           >> 2
+```
+
 #### construct
-Basically, you can use selectors to assemble whatever you want, and of course, the result is still a json ;)
+Basically, you can use selectors to assemble whatever you want, and of course, the result is still a json ;) The members of a constructed object keep the order of the selectors, a repeated key keeps its first position and takes the last value.
 ```text
 {name.first,age,"murphys":friends.#(last="Murphy")#.first}
 [name.first,age,children.0]
 ```
-*/
 
-#[cfg(feature = "wild")]
-extern crate regex;
-#[cfg(feature = "wild")]
-mod wild;
+A `!` followed by a JSON value is a literal, it can be used in selectors to add constants, or as a path by itself.
+```text
+{"version":!2,"ok":!true,"name":name.first}   >> {"version":2,"ok":true,"name":"Tom"}
+[!"first",name.first]                         >> ["first","Tom"]
+!{"a":[1,2]}.a                                >> [1,2]
+```
+
+#### modifiers
+A modifier is a path component that performs custom processing on the json. The built-in modifiers are `@reverse`, `@flatten`, `@keys`, `@values`, `@join` and `@this`. A modifier may accept an optional json argument after a colon, such as `@flatten:{"deep":true}`. Unknown modifiers are looked up as plain keys.
 
+```text
+children.@reverse           >> ["Jack","Alex","Sara"]
+children.@reverse.0         >> "Jack"
+name.@keys                  >> ["first","last"]
+name.@values                >> ["Tom","Anderson"]
+friends.@join.age           >> 47
+@this.age                   >> 37
+friends.#.nets|@flatten     >> ["ig","fb","tw","fb","tw","ig","tw"]
+```
+
+Custom modifiers can be registered globally with [`Modifiers::register`](struct.Modifiers.html#method.register),
+or passed to a single call with [`get_with`](fn.get_with.html).
+*/
+
+mod diff;
+mod edit;
 mod element;
+mod merge;
+pub mod merge_patch;
+mod modifiers;
 mod number;
+mod options;
 mod parser;
+pub mod patch;
 mod path;
+mod project;
+mod redact;
 mod unescape;
 mod util;
 mod value;
+mod wild;
 
 use std::result;
 
+pub use diff::{diff, diff_with, Change, ChangeKind, DiffOptions};
+pub use edit::{delete, insert, insert_raw, set, set_raw};
 #[doc(hidden)]
 pub use element::compound_u8;
+pub use merge::{merge, ArrayStrategy, ConflictFn, MergeOptions};
+pub use modifiers::{ModifierFn, Modifiers};
 pub use number::Number;
+pub use options::Options;
 pub use path::Path;
-pub use unescape::unescape;
+pub use project::project;
+pub use redact::{redact, Mask};
+pub use unescape::{escape, unescape};
 pub use value::Value;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -118,6 +267,10 @@ pub enum Error {
     ObjectKey,
     Object,
     Array,
+    /// The JSON Patch document is malformed.
+    Patch,
+    /// The `test` operation of a JSON Patch failed.
+    Test,
 }
 
 pub type Result<T> = result::Result<T, Error>;
@@ -151,8 +304,20 @@ pub type Result<T> = result::Result<T, Error>;
 /// let data = r#"someinvalidstring"#;
 /// let v = ajson::get(data, "name").unwrap().unwrap();
 /// ```
+/// The regex operators `=~` and `!~` need the `regex` feature, without it
+/// `get` returns `Error::Path` for the paths using them.
 pub fn get<'a>(json: &'a str, path: &'a str) -> Result<Option<Value<'a>>> {
-    let path = path::Path::from_slice(path.as_bytes())?;
+    get_with(json, path, Options::new())
+}
+
+/// Same as [`get`](fn.get.html), but with the given [`Options`](struct.Options.html).
+pub fn get_with<'a>(
+    json: &'a str,
+    path: &'a str,
+    options: Options<'a>,
+) -> Result<Option<Value<'a>>> {
+    let options = options.root(json.as_bytes());
+    let path = path::Path::from_slice_with(path.as_bytes(), options)?;
     let (a, _left) = parser::bytes_get(json.as_bytes(), &path)?;
     Ok(a.map(|el| el.to_value()))
 }
@@ -172,7 +337,7 @@ pub fn get<'a>(json: &'a str, path: &'a str) -> Result<Option<Value<'a>>> {
 ///     Ok(())
 /// }
 /// ```
-pub fn parse(json: &str) -> Result<Option<Value>> {
+pub fn parse(json: &str) -> Result<Option<Value<'_>>> {
     let (parsed, _left) = element::read_one(json.as_bytes())?;
 
     Ok(parsed.map(|el| el.to_value()))
@@ -0,0 +1,206 @@
+use std::{mem, str};
+
+use crate::{
+    edit,
+    element::{self, Element},
+    util, Result, Value,
+};
+
+/// Resolves the conflict at the path when the base and the overlay have
+/// values of different types, it returns the merged value.
+pub type ConflictFn = fn(&str, &Value<'_>, &Value<'_>) -> Value<'static>;
+
+/// How [`merge`](fn.merge.html) merges two arrays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayStrategy<'a> {
+    /// The array of the overlay replaces the one of the base.
+    Replace,
+    /// The elements of the overlay are appended to the base.
+    Concat,
+    /// The objects which have the same value at the field are merged, the
+    /// others are appended. The elements without the field are appended
+    /// unless an equal one is in the base already.
+    UnionBy(&'a str),
+}
+
+/// Options of [`merge`](fn.merge.html).
+/// ```
+/// use ajson::{ArrayStrategy, MergeOptions, Value};
+///
+/// fn keep_base(_path: &str, base: &Value, _overlay: &Value) -> Value<'static> {
+///     base.clone().into_owned()
+/// }
+///
+/// let options = MergeOptions::new()
+///     .array("plugins", ArrayStrategy::Concat)
+///     .conflict(keep_base);
+/// let json = ajson::merge(
+///     r#"{"plugins": ["a"], "db": {"host": "localhost"}}"#,
+///     r#"{"plugins": ["b"], "db": "postgres://db"}"#,
+///     options,
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     json,
+///     r#"{"plugins": ["a","b"], "db": {"host": "localhost"}}"#
+/// );
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MergeOptions<'a> {
+    arrays:   Vec<(&'a str, ArrayStrategy<'a>)>,
+    conflict: Option<ConflictFn>,
+}
+
+impl<'a> MergeOptions<'a> {
+    pub const fn new() -> MergeOptions<'a> {
+        MergeOptions {
+            arrays:   Vec::new(),
+            conflict: None,
+        }
+    }
+
+    /// Merges the arrays at the path with the strategy, the others are
+    /// replaced. The path is made of the keys escaped as in the paths of
+    /// [`get`](fn.get.html), and `#` for the elements of arrays merged by
+    /// [`ArrayStrategy::UnionBy`](enum.ArrayStrategy.html), such as
+    /// `services.#.ports`.
+    pub fn array(mut self, path: &'a str, strategy: ArrayStrategy<'a>) -> Self {
+        self.arrays.push((path, strategy));
+        self
+    }
+
+    /// Calls the function when the values have different types, the value
+    /// of the overlay wins otherwise.
+    pub fn conflict(mut self, f: ConflictFn) -> Self {
+        self.conflict = Some(f);
+        self
+    }
+
+    fn strategy(&self, path: &str) -> ArrayStrategy<'a> {
+        self.arrays
+            .iter()
+            .rev()
+            .find(|(p, _)| *p == path)
+            .map_or(ArrayStrategy::Replace, |(_, strategy)| *strategy)
+    }
+
+    fn merge_values(&self, path: &str, base: Element, overlay: Element) -> Result<String> {
+        match (&base, &overlay) {
+            (Element::Object(b), Element::Object(o)) => self.objects(path, element::text(b), o),
+            (Element::Array(b), Element::Array(o)) => self.arrays(path, element::text(b), o),
+            _ if mem::discriminant(&base) != mem::discriminant(&overlay) => match self.conflict {
+                Some(f) => Ok(f(path, &base.to_value(), &overlay.to_value())
+                    .to_json()
+                    .into_owned()),
+                None => Ok(overlay.raw_str().to_string()),
+            },
+            _ => Ok(overlay.raw_str().to_string()),
+        }
+    }
+
+    fn objects(&self, path: &str, base: &str, overlay: &[u8]) -> Result<String> {
+        let mut json = base.to_string();
+        for ((key, esc), value) in element::object_members(overlay)? {
+            let name = element::member_key(key, esc);
+            let bytes = json.as_bytes();
+            let children = edit::children(bytes, bytes)?;
+
+            json = match children.iter().rposition(|child| child.has_key(&name)) {
+                Some(i) => {
+                    let path = util::join_path(path, &util::escape_key(&name));
+                    let merged = self.merge_values(&path, children[i].value.clone(), value)?;
+                    edit::splice(&json, children[i].value_span(), &merged)
+                }
+                None => {
+                    let member = format!("{}:{}", element::text(key), value.raw_str());
+                    edit::append(&json, 0..json.len(), &[member])?
+                }
+            };
+        }
+        Ok(json)
+    }
+
+    fn arrays(&self, path: &str, base: &str, overlay: &[u8]) -> Result<String> {
+        let field = match self.strategy(path) {
+            ArrayStrategy::Replace => return Ok(element::text(overlay).to_string()),
+            ArrayStrategy::Concat => {
+                let added: Vec<String> = element::array_elements(overlay)?
+                    .iter()
+                    .map(|value| value.raw_str().to_string())
+                    .collect();
+                return edit::append(base, 0..base.len(), &added);
+            }
+            ArrayStrategy::UnionBy(field) => field,
+        };
+
+        let path = util::join_path(path, "#");
+        let mut json = base.to_string();
+        for value in element::array_elements(overlay)? {
+            let bytes = json.as_bytes();
+            let children = edit::children(bytes, bytes)?;
+
+            let key = element::field_key(&value, field)?.map(|(key, _)| key);
+            let mut found = None;
+            for (i, child) in children.iter().enumerate() {
+                let matched = match &key {
+                    Some(key) => {
+                        element::field_key(&child.value, field)?.is_some_and(|(k, _)| &k == key)
+                    }
+                    None => element::equal(&child.value, &value)?,
+                };
+                if matched {
+                    found = Some(i);
+                    break;
+                }
+            }
+
+            json = match (found, &key) {
+                (Some(i), Some(_)) => {
+                    let merged = self.merge_values(&path, children[i].value.clone(), value)?;
+                    edit::splice(&json, children[i].value_span(), &merged)
+                }
+                // an equal element is there already
+                (Some(_), None) => continue,
+                (None, _) => edit::append(&json, 0..json.len(), &[value.raw_str().to_string()])?,
+            };
+        }
+        Ok(json)
+    }
+}
+
+/// Merges the overlay into the base and returns the new JSON. The objects
+/// are merged recursively, the other values of the overlay replace the ones
+/// of the base, and the arrays are merged by the strategies of the
+/// [`MergeOptions`](struct.MergeOptions.html). The formatting of the base
+/// is kept.
+/// ```
+/// use ajson::{ArrayStrategy, MergeOptions, Result};
+/// fn main() -> Result<()> {
+///     let defaults = r#"{"port": 80, "tls": {"on": false, "cert": "a.pem"}, "users": [{"id": 1, "role": "admin"}]}"#;
+///     let env = r#"{"tls": {"on": true}, "users": [{"id": 1, "role": "owner"}, {"id": 2, "role": "dev"}]}"#;
+///
+///     let options = MergeOptions::new().array("users", ArrayStrategy::UnionBy("id"));
+///     assert_eq!(
+///         ajson::merge(defaults, env, options)?,
+///         r#"{"port": 80, "tls": {"on": true, "cert": "a.pem"}, "users": [{"id": 1, "role": "owner"},{"id": 2, "role": "dev"}]}"#
+///     );
+///     Ok(())
+/// }
+/// ```
+pub fn merge(base: &str, overlay: &str, options: MergeOptions) -> Result<String> {
+    let bytes = base.as_bytes();
+    let overlay = match element::read_one(overlay.as_bytes())?.0 {
+        Some(overlay) => overlay,
+        None => return Ok(base.to_string()),
+    };
+
+    match element::read_one(bytes)?.0 {
+        Some(element) => {
+            let raw = element.raw().unwrap_or_default();
+            let start = edit::offset(bytes, raw);
+            let merged = options.merge_values("", element, overlay)?;
+            Ok(edit::splice(base, start..start + raw.len(), &merged))
+        }
+        None => Ok(overlay.raw_str().to_string()),
+    }
+}
@@ -0,0 +1,119 @@
+//! Applies and creates [JSON Merge Patch](https://tools.ietf.org/html/rfc7396)
+//! documents.
+//!
+//! ```
+//! use ajson::Result;
+//! fn main() -> Result<()> {
+//!     let json = r#"{"name": "Tom", "age": 37, "tags": ["a"]}"#;
+//!     let patch = r#"{"age": null, "tags": ["b"], "city": "Paris"}"#;
+//!
+//!     let patched = ajson::merge_patch::apply(json, patch)?;
+//!     assert_eq!(patched, r#"{"name": "Tom", "tags": ["b"], "city":"Paris"}"#);
+//!     assert_eq!(
+//!         ajson::merge_patch::diff(json, &patched)?,
+//!         r#"{"tags":["b"],"city":"Paris","age":null}"#
+//!     );
+//!     Ok(())
+//! }
+//! ```
+
+use std::str;
+
+use crate::{
+    edit,
+    element::{self, Element},
+    util, Error, Result,
+};
+
+/// Applies the merge patch to the JSON and returns the new JSON. The
+/// members of the patch replace the ones of the JSON, except that `null`
+/// deletes the member and the objects are merged recursively. A patch
+/// which isn't an object replaces the whole JSON.
+/// ```
+/// let json = r#"{"a": {"b": 1, "c": 2}}"#;
+/// let json = ajson::merge_patch::apply(json, r#"{"a": {"b": null, "d": 3}}"#).unwrap();
+/// assert_eq!(json, r#"{"a": {"c": 2,"d":3}}"#);
+/// ```
+pub fn apply(json: &str, patch: &str) -> Result<String> {
+    let patch = util::trim_whitespace_u8(patch.as_bytes());
+    if patch.first() != Some(&b'{') {
+        return Ok(element::text(patch).to_string());
+    }
+
+    let mut json = match util::trim_whitespace_u8(json.as_bytes()).first() {
+        Some(b'{') => json.to_string(),
+        _ => "{}".to_string(),
+    };
+
+    for ((key, esc), value) in element::object_members(patch)? {
+        let bytes = json.as_bytes();
+        let root = util::trim_whitespace_u8(bytes);
+        let start = edit::offset(bytes, root);
+        let span = start..start + root.len();
+
+        let name = element::member_key(key, esc);
+        let children = edit::children(bytes, root)?;
+        let found = children.iter().rposition(|child| child.has_key(&name));
+
+        json = match (found, value) {
+            (Some(i), Element::Null(_)) => edit::remove_children(&json, span, &[i])?,
+            (None, Element::Null(_)) => continue,
+            (Some(i), value) => {
+                let span = children[i].value_span();
+                let merged = apply(&json[span.clone()], value.raw_str())?;
+                edit::splice(&json, span, &merged)
+            }
+            (None, value) => {
+                let member = format!("{}:{}", element::text(key), apply("", value.raw_str())?);
+                edit::append(&json, span, &[member])?
+            }
+        };
+    }
+
+    Ok(json)
+}
+
+/// Returns the merge patch which turns the JSON `a` into `b`, the members
+/// equal in both are left out and `{}` is returned if nothing differs.
+/// ```
+/// let a = r#"{"name": "Tom", "age": 37, "address": {"city": "Paris", "zip": "75001"}}"#;
+/// let b = r#"{"name": "Tom", "address": {"city": "Lyon", "zip": "75001"}}"#;
+/// assert_eq!(
+///     ajson::merge_patch::diff(a, b).unwrap(),
+///     r#"{"address":{"city":"Lyon"},"age":null}"#
+/// );
+/// ```
+/// A merge patch can't set a member to `null`, nor keep the `null`s in a
+/// new object, so such a `b` can't be reached by applying the diff.
+pub fn diff(a: &str, b: &str) -> Result<String> {
+    let a = element::read_one(a.as_bytes())?.0;
+    let b = element::read_one(b.as_bytes())?.0.ok_or(Error::Eof)?;
+
+    let (a, b) = match (a, &b) {
+        (Some(Element::Object(a)), Element::Object(b)) => (a, *b),
+        _ => return Ok(b.raw_str().to_string()),
+    };
+
+    let a_members = element::members_by_key(a)?;
+    let b_members = element::members_by_key(b)?;
+
+    let mut members = Vec::new();
+    for ((key, esc), value) in element::object_members(b)? {
+        let value = match a_members.get(&element::member_key(key, esc)) {
+            Some(old) if element::equal(old, &value)? => continue,
+            Some(Element::Object(old)) if matches!(value, Element::Object(_)) => {
+                diff(element::text(old), value.raw_str())?
+            }
+            _ => value.raw_str().to_string(),
+        };
+        members.push(format!("{}:{}", element::text(key), value));
+    }
+
+    for ((key, esc), _) in element::object_members(a)? {
+        if !b_members.contains_key(&element::member_key(key, esc)) {
+            members.push(format!("{}:null", element::text(key)));
+        }
+    }
+
+    Ok(format!("{{{}}}", members.join(",")))
+}
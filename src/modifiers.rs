@@ -0,0 +1,275 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    str,
+    sync::{OnceLock, RwLock},
+};
+
+use crate::{
+    element::{self, Element, MapBuilder},
+    parser,
+    path::{Modifier, Path},
+    unescape, Options, Result, Value,
+};
+
+/// A user-defined modifier. It takes the current value and the optional
+/// argument after the colon, e.g. `"mask:4"` calls it with `Some("4")`.
+pub type ModifierFn = fn(&Value<'_>, Option<&str>) -> Value<'static>;
+
+type BuiltinFn = for<'a> fn(Element<'a>, Option<&'a [u8]>) -> Result<Option<Element<'a>>>;
+
+const BUILTINS: [(&str, BuiltinFn); 6] = [
+    ("this", this),
+    ("reverse", reverse),
+    ("flatten", flatten),
+    ("keys", keys),
+    ("values", values),
+    ("join", join),
+];
+
+/// A registry of user-defined modifiers.
+/// ```
+/// use ajson::{Modifiers, Value};
+///
+/// fn mask(_: &Value, _: Option<&str>) -> Value<'static> {
+///     Value::String("***".into())
+/// }
+///
+/// Modifiers::register("mask", mask);
+/// let v = ajson::get(r#"{"password":"secret"}"#, "password.@mask")
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(v, "***");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Modifiers {
+    fns: HashMap<String, ModifierFn>,
+}
+
+impl Modifiers {
+    pub fn new() -> Modifiers {
+        Default::default()
+    }
+
+    /// Adds a modifier to this registry, it can be used by passing the
+    /// registry to [`Options::modifiers`](struct.Options.html#method.modifiers).
+    pub fn insert(&mut self, name: &str, f: ModifierFn) {
+        self.fns.insert(name.to_string(), f);
+    }
+
+    /// Registers a modifier globally, it's available to all the paths.
+    pub fn register(name: &str, f: ModifierFn) {
+        registry()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name, f);
+    }
+
+    fn get(&self, name: &[u8]) -> Option<ModifierFn> {
+        str::from_utf8(name)
+            .ok()
+            .and_then(|name| self.fns.get(name))
+            .copied()
+    }
+}
+
+fn registry() -> &'static RwLock<Modifiers> {
+    static REGISTRY: OnceLock<RwLock<Modifiers>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+enum Func {
+    Builtin(BuiltinFn),
+    User(ModifierFn),
+}
+
+/// Finds the modifier by name, from the options, the global registry
+/// and then the built-in ones.
+fn find(name: &[u8], opts: Options) -> Option<Func> {
+    if let Some(f) = opts.modifiers.and_then(|m| m.get(name)) {
+        return Some(Func::User(f));
+    }
+
+    if let Some(f) = registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+    {
+        return Some(Func::User(f));
+    }
+
+    BUILTINS
+        .iter()
+        .find(|(n, _)| n.as_bytes() == name)
+        .map(|(_, f)| Func::Builtin(*f))
+}
+
+pub fn exists(name: &[u8], opts: Options) -> bool {
+    find(name, opts).is_some()
+}
+
+pub fn apply<'a>(
+    modifier: &Modifier<'a>,
+    element: Element<'a>,
+    opts: Options,
+) -> Result<Option<Element<'a>>> {
+    match find(modifier.name, opts) {
+        Some(Func::Builtin(f)) => f(element, modifier.args),
+        Some(Func::User(f)) => {
+            let args = modifier.args.and_then(|args| str::from_utf8(args).ok());
+            Ok(Some(Element::Value(f(&element.to_value(), args))))
+        }
+        None => Ok(None),
+    }
+}
+
+fn bool_arg(args: Option<&[u8]>, key: &str) -> Result<bool> {
+    let args = match args {
+        Some(args) => args,
+        None => return Ok(false),
+    };
+
+    let path = Path::from_slice(key.as_bytes())?;
+    match parser::bytes_get(args, &path)? {
+        (Some(Element::Boolean(b)), _) => Ok(b[0] == b't'),
+        _ => Ok(false),
+    }
+}
+
+/// `@this` returns the current element.
+fn this<'a>(element: Element<'a>, _: Option<&'a [u8]>) -> Result<Option<Element<'a>>> {
+    Ok(Some(element))
+}
+
+/// `@reverse` reverses an array or the members of an object.
+fn reverse<'a>(element: Element<'a>, _: Option<&'a [u8]>) -> Result<Option<Element<'a>>> {
+    let element = match element {
+        Element::Array(s) => {
+            let mut elements = element::array_elements(s)?;
+            elements.reverse();
+            Element::List(elements)
+        }
+        Element::List(mut elements) => {
+            elements.reverse();
+            Element::List(elements)
+        }
+        Element::Object(s) => element::object_members(s)?
+            .into_iter()
+            .rev()
+            .map(|((key, esc), value)| ((&key[1..key.len() - 1], esc), value))
+            .collect::<MapBuilder>()
+            .build(),
+        Element::Map(mut m) => {
+            m.reverse();
+            Element::Map(m)
+        }
+        element => element,
+    };
+
+    Ok(Some(element))
+}
+
+/// `@flatten` flattens an array with child arrays, `@flatten:{"deep":true}`
+/// flattens all the nested arrays.
+fn flatten<'a>(element: Element<'a>, args: Option<&'a [u8]>) -> Result<Option<Element<'a>>> {
+    let deep = bool_arg(args, "deep")?;
+    let elements = match element {
+        Element::Array(s) => element::array_elements(s)?,
+        Element::List(elements) => elements,
+        element => return Ok(Some(element)),
+    };
+
+    let mut flat = Vec::with_capacity(elements.len());
+    for element in elements {
+        flatten_into(element, deep, &mut flat)?;
+    }
+
+    Ok(Some(Element::List(flat)))
+}
+
+fn flatten_into<'a>(element: Element<'a>, deep: bool, flat: &mut Vec<Element<'a>>) -> Result<()> {
+    let elements = match element {
+        Element::Array(s) => element::array_elements(s)?,
+        Element::List(elements) => elements,
+        element => {
+            flat.push(element);
+            return Ok(());
+        }
+    };
+
+    for element in elements {
+        if deep {
+            flatten_into(element, deep, flat)?;
+        } else {
+            flat.push(element);
+        }
+    }
+
+    Ok(())
+}
+
+/// `@keys` returns the keys of an object.
+fn keys<'a>(element: Element<'a>, _: Option<&'a [u8]>) -> Result<Option<Element<'a>>> {
+    let keys = match element {
+        Element::Object(s) => element::object_members(s)?
+            .into_iter()
+            .map(|((key, esc), _)| Element::String(key, esc))
+            .collect(),
+        Element::Map(m) => m
+            .into_iter()
+            .map(|((key, esc), _)| {
+                let key = match esc {
+                    true => Cow::Owned(unescape(key)),
+                    false => Cow::Borrowed(unsafe { str::from_utf8_unchecked(key) }),
+                };
+                Element::Value(Value::String(key))
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    Ok(Some(Element::List(keys)))
+}
+
+/// `@values` returns the values of an object.
+fn values<'a>(element: Element<'a>, _: Option<&'a [u8]>) -> Result<Option<Element<'a>>> {
+    let values = match element {
+        Element::Object(s) => element::object_members(s)?
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect(),
+        Element::Map(m) => m.into_iter().map(|(_, value)| value).collect(),
+        _ => vec![],
+    };
+
+    Ok(Some(Element::List(values)))
+}
+
+/// `@join` joins an array of objects into a single object, the latter
+/// members win over the former ones.
+fn join<'a>(element: Element<'a>, _: Option<&'a [u8]>) -> Result<Option<Element<'a>>> {
+    let elements = match element {
+        Element::Array(s) => element::array_elements(s)?,
+        Element::List(elements) => elements,
+        element => return Ok(Some(element)),
+    };
+
+    let mut map = MapBuilder::new();
+    for element in elements {
+        match element {
+            Element::Object(s) => {
+                for ((key, esc), value) in element::object_members(s)? {
+                    map.insert((&key[1..key.len() - 1], esc), value);
+                }
+            }
+            Element::Map(m) => {
+                for (key, value) in m {
+                    map.insert(key, value);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(Some(map.build()))
+}
@@ -1,4 +1,5 @@
-use std::convert::From;
+use std::{borrow::Cow, convert::From};
+
 const MIN_UINT_53: u64 = 0;
 const MAX_UINT_53: u64 = 4503599627370495;
 const MIN_INT_53: i64 = -2251799813685248;
@@ -11,15 +12,19 @@ const ZERO_FLOAT_F32: f32 = 0.0;
 // const ZERO_INT_I32: i32 = 0;
 // const ZERO_INT_U32: u32 = 0;
 
+/// Represents a JSON number as its text, either borrowed from the JSON or
+/// owned when it's built with `Value::from`.
+///
+/// Since 0.4 the variants hold a `Cow<'a, str>` instead of a `&'a str`.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Number<'a> {
-    F64(&'a str),
-    U64(&'a str),
-    I64(&'a str),
+    F64(Cow<'a, str>),
+    U64(Cow<'a, str>),
+    I64(Cow<'a, str>),
 }
 
 impl<'a> From<&'a [u8]> for Number<'a> {
-    fn from(v: &[u8]) -> Number {
+    fn from(v: &[u8]) -> Number<'_> {
         let sign = match v.first() {
             Some(b'-') => true,
             None => panic!("invalid number"),
@@ -32,7 +37,7 @@ impl<'a> From<&'a [u8]> for Number<'a> {
             let &b = unsafe { v.get_unchecked(i) };
             match b {
                 b'0'..=b'9' => (),
-                b'.' => float = true,
+                b'.' | b'e' | b'E' | b'+' | b'-' => float = true,
                 _ => {
                     break;
                 }
@@ -43,11 +48,11 @@ impl<'a> From<&'a [u8]> for Number<'a> {
         let s = unsafe { std::str::from_utf8_unchecked(v.get_unchecked(0..i)) };
 
         if float {
-            Number::F64(s)
+            Number::F64(Cow::Borrowed(s))
         } else if sign {
-            Number::I64(s)
+            Number::I64(Cow::Borrowed(s))
         } else {
-            Number::U64(s)
+            Number::U64(Cow::Borrowed(s))
         }
     }
 }
@@ -61,6 +66,14 @@ impl<'a> Number<'a> {
         }
     }
 
+    pub fn into_owned(self) -> Number<'static> {
+        match self {
+            Number::F64(s) => Number::F64(Cow::Owned(s.into_owned())),
+            Number::U64(s) => Number::U64(Cow::Owned(s.into_owned())),
+            Number::I64(s) => Number::I64(Cow::Owned(s.into_owned())),
+        }
+    }
+
     pub fn to_f64(&self) -> f64 {
         match self {
             Number::F64(s) => s.parse().unwrap_or(ZERO_FLOAT),
@@ -0,0 +1,55 @@
+use crate::Modifiers;
+
+/// Options of a single call, see [`get_with`](fn.get_with.html).
+/// ```
+/// use ajson::{Modifiers, Options, Value};
+///
+/// fn upper(v: &Value, _: Option<&str>) -> Value<'static> {
+///     Value::String(v.to_string().to_uppercase().into())
+/// }
+///
+/// let mut modifiers = Modifiers::new();
+/// modifiers.insert("upper", upper);
+///
+/// let options = Options::new().modifiers(&modifiers);
+/// let v = ajson::get_with(r#"{"name":"ajson"}"#, "name.@upper", options)
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(v, "AJSON");
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options<'a> {
+    pub(crate) modifiers:        Option<&'a Modifiers>,
+    /// The document which `$` refers to in queries.
+    pub(crate) root:             Option<&'a [u8]>,
+    pub(crate) case_insensitive: bool,
+}
+
+impl<'a> Options<'a> {
+    pub const fn new() -> Options<'a> {
+        Options {
+            modifiers:        None,
+            root:             None,
+            case_insensitive: false,
+        }
+    }
+
+    /// Makes the given modifiers available to the path, they take
+    /// precedence over the globally registered and the built-in ones.
+    pub fn modifiers(mut self, modifiers: &'a Modifiers) -> Self {
+        self.modifiers = Some(modifiers);
+        self
+    }
+
+    /// Matches the keys, and the strings compared by `==`, `!=`, `%` and
+    /// `!%` in queries, case-insensitively.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub(crate) fn root(mut self, root: &'a [u8]) -> Self {
+        self.root = Some(root);
+        self
+    }
+}
@@ -1,14 +1,18 @@
-use std::{collections::HashMap, str};
+use std::{
+    collections::{HashMap, VecDeque},
+    str, vec,
+};
 
 use crate::{
     element,
-    element::Element,
-    path::{Path, SubSelector},
+    element::{Element, MapBuilder},
+    modifiers,
+    path::{Path, Slice},
     value::Value,
     Error, Result,
 };
 
-pub fn bytes_to_vec(mut bytes: &[u8]) -> Result<Vec<Value>> {
+pub fn bytes_to_vec(mut bytes: &[u8]) -> Result<Vec<Value<'_>>> {
     let mut arr = Vec::new();
     let mut i = 0;
     while i < bytes.len() {
@@ -40,7 +44,7 @@ pub fn bytes_to_vec(mut bytes: &[u8]) -> Result<Vec<Value>> {
     Ok(arr)
 }
 
-pub fn bytes_to_map(mut bytes: &[u8]) -> Result<HashMap<&str, Value>> {
+pub fn bytes_to_map(mut bytes: &[u8]) -> Result<HashMap<&str, Value<'_>>> {
     let mut m = HashMap::new();
 
     let mut i = 0;
@@ -94,14 +98,49 @@ pub fn bytes_to_map(mut bytes: &[u8]) -> Result<HashMap<&str, Value>> {
 }
 
 pub fn bytes_get<'a>(bytes: &'a [u8], path: &Path<'a>) -> Result<(Option<Element<'a>>, &'a [u8])> {
-    if !path.ok || bytes.is_empty() {
+    if !path.ok {
         return Ok((None, "".as_bytes()));
     }
 
+    if let Some(literal) = path.literal {
+        return Ok((literal_get(literal, path)?, bytes));
+    }
+
+    if bytes.is_empty() {
+        return Ok((None, "".as_bytes()));
+    }
+
+    if path.lines {
+        return lines_get(bytes, path);
+    }
+
+    if let Some(modifier) = &path.modifier {
+        let (element, left) = element::read_one(bytes)?;
+        let element = match element {
+            Some(element) => modifiers::apply(modifier, element, path.opts)?,
+            None => None,
+        };
+
+        return match element {
+            Some(element) if path.more => {
+                let next = path.parse_next()?;
+                Ok((element_get(element, &next)?, left))
+            }
+            element => Ok((element, left)),
+        };
+    }
+
+    if path.descent {
+        return match element::read_one(bytes)? {
+            (Some(element), left) => Ok((descent_get(element, path)?, left)),
+            (None, left) => Ok((None, left)),
+        };
+    }
+
     if path.has_selectors() {
         let element = match path.arrsel {
-            true => select_to_array(bytes, path.borrow_selectors())?,
-            false => select_to_object(bytes, path.borrow_selectors())?,
+            true => select_to_array(bytes, path)?,
+            false => select_to_object(bytes, path)?,
         };
 
         match element {
@@ -146,24 +185,24 @@ pub fn bytes_get<'a>(bytes: &'a [u8], path: &Path<'a>) -> Result<(Option<Element
     Ok((None, "".as_bytes()))
 }
 
-fn select_to_object<'a>(input: &'a [u8], sels: &[SubSelector<'a>]) -> Result<Option<Element<'a>>> {
-    let mut map = HashMap::new();
+fn select_to_object<'a>(input: &'a [u8], parent: &Path<'a>) -> Result<Option<Element<'a>>> {
+    let mut map = MapBuilder::new();
 
-    for sel in sels {
-        let path = Path::from_slice(sel.path)?;
+    for sel in parent.borrow_selectors() {
+        let path = parent.parse_sub(sel.path)?;
         if let (Some(sub_pv), _) = bytes_get(input, &path)? {
-            map.insert((sel.name, false), sub_pv);
+            map.insert((sel.name, sel.name.contains(&b'\\')), sub_pv);
         }
     }
 
-    Ok(Some(Element::Map(map)))
+    Ok(Some(map.build()))
 }
 
-fn select_to_array<'a>(input: &'a [u8], sels: &[SubSelector<'a>]) -> Result<Option<Element<'a>>> {
+fn select_to_array<'a>(input: &'a [u8], parent: &Path<'a>) -> Result<Option<Element<'a>>> {
     let mut list = Vec::new();
 
-    for sel in sels {
-        let path = Path::from_slice(sel.path)?;
+    for sel in parent.borrow_selectors() {
+        let path = parent.parse_sub(sel.path)?;
         if let (Some(sub_pv), _) = bytes_get(input, &path)? {
             list.push(sub_pv)
         }
@@ -172,7 +211,10 @@ fn select_to_array<'a>(input: &'a [u8], sels: &[SubSelector<'a>]) -> Result<Opti
     Ok(Some(Element::List(list)))
 }
 
-fn element_ref_get<'a>(element: &Element<'a>, path: &Path<'a>) -> Result<Option<Element<'a>>> {
+pub(crate) fn element_ref_get<'a>(
+    element: &Element<'a>,
+    path: &Path<'a>,
+) -> Result<Option<Element<'a>>> {
     if !path.ok {
         return Ok(None);
     }
@@ -182,6 +224,18 @@ fn element_ref_get<'a>(element: &Element<'a>, path: &Path<'a>) -> Result<Option<
             let (a, _b) = bytes_get(s, path)?;
             Ok(a)
         }
+        Element::Value(v) => value_get(v, path),
+        _ => Ok(None),
+    }
+}
+
+/// Gets from an owned value, the result is owned too.
+fn value_get<'a>(value: &Value, path: &Path) -> Result<Option<Element<'a>>> {
+    match value {
+        Value::Array(s) | Value::Object(s) => {
+            let (a, _b) = bytes_get(s.as_bytes(), path)?;
+            Ok(a.map(|el| Element::Value(el.to_value().into_owned())))
+        }
         _ => Ok(None),
     }
 }
@@ -192,11 +246,30 @@ fn element_get<'a>(element: Element<'a>, path: &Path<'a>) -> Result<Option<Eleme
     }
 
     let next_path = path.parse_next()?;
+
+    if let Some(modifier) = &path.modifier {
+        return match modifiers::apply(modifier, element, path.opts)? {
+            Some(element) if path.more => element_get(element, &next_path),
+            element => Ok(element),
+        };
+    }
+
+    if path.descent {
+        return descent_get(element, path);
+    }
+
+    if let Some(literal) = path.literal {
+        return literal_get(literal, path);
+    }
+
     match element {
         Element::Array(s) | Element::Object(s) => {
             let (a, _b) = bytes_get(s, path)?;
             Ok(a)
         }
+        Element::Map(m) if path.arrch && path.borrow_query().on => {
+            members_query(m.into_iter(), path)
+        }
         Element::Map(m) => {
             for (key, value) in m.into_iter() {
                 if path.is_match(key.0, key.1) {
@@ -210,37 +283,37 @@ fn element_get<'a>(element: Element<'a>, path: &Path<'a>) -> Result<Option<Eleme
             Ok(None)
         }
         Element::List(elements) => {
-            let query = path.borrow_query();
-            let query_list = (query.on && query.all) || (!query.on && path.more);
-            let query_first = query.on && !query.all;
+            if let Some(slice) = &path.slice {
+                let indices = slice.indices(elements.len());
+                let elements = pick(elements, 0, &indices);
+                return iter_get(&mut elements.into_iter().map(Ok), path);
+            }
 
-            if query_first {
-                if !elements.is_empty() {
-                    let first = elements.into_iter().next().unwrap();
-                    if path.more {
-                        return element_get(first, &next_path);
+            if !path.arrch {
+                if let Ok(idx) = str::from_utf8(path.part)
+                    .map_err(|_| Error::Path)?
+                    .parse::<isize>()
+                {
+                    let idx = match idx < 0 {
+                        true => elements.len() as isize + idx,
+                        false => idx,
+                    };
+                    if idx < 0 {
+                        return Ok(None);
                     }
-                    return Ok(Some(first));
+
+                    return match elements.into_iter().nth(idx as usize) {
+                        Some(element) if path.more => element_get(element, &next_path),
+                        element => Ok(element),
+                    };
                 }
 
                 return Ok(None);
             }
 
-            if query_list {
-                if path.more {
-                    let mut results = vec![];
-                    for element in elements.into_iter() {
-                        if let Some(sub) = element_get(element, &next_path)? {
-                            results.push(sub);
-                        }
-                    }
-                    return Ok(Some(Element::List(results)));
-                }
-                return Ok(Some(Element::List(elements)));
-            }
-
-            Ok(Some(Element::Count(elements.len())))
+            iter_get(&mut elements.into_iter().map(Ok), path)
         }
+        Element::Value(v) => value_get(&v, path),
         _ => Ok(None),
     }
 }
@@ -250,6 +323,13 @@ fn object_bytes_get<'a>(
     mut input: &'a [u8],
     path: &Path<'a>,
 ) -> Result<(Option<Element<'a>>, &'a [u8])> {
+    if path.arrch && path.borrow_query().on {
+        let members = element::object_members(input)?
+            .into_iter()
+            .map(|((key, esc), value)| ((&key[1..key.len() - 1], esc), value));
+        return Ok((members_query(members, path)?, "".as_bytes()));
+    }
+
     let mut i = 1;
 
     while i < input.len() {
@@ -289,75 +369,275 @@ fn object_bytes_get<'a>(
     Ok((None, "".as_bytes()))
 }
 
+/// Queries the values of an object. `#(...)` gets the first matched value,
+/// and `#(...)#` keeps the matched members, the path after `#.` is applied
+/// to each value and the path after `|` to the whole object.
+fn members_query<'a, I>(members: I, path: &Path<'a>) -> Result<Option<Element<'a>>>
+where
+    I: Iterator<Item = ((&'a [u8], bool), Element<'a>)>,
+{
+    let query = path.borrow_query();
+    let (each_path, list_path) = match query.all && path.more {
+        true => path.parse_next_piped()?,
+        false => (path.parse_next()?, Path::default()),
+    };
+
+    let mut map = MapBuilder::new();
+    for (key, mut value) in members {
+        if !query.match_element(&value)? {
+            continue;
+        }
+
+        if each_path.ok {
+            match element_get(value, &each_path)? {
+                Some(v) => value = v,
+                None => continue,
+            }
+        }
+
+        if !query.all {
+            return Ok(Some(value));
+        }
+        map.insert(key, value);
+    }
+
+    if !query.all {
+        return Ok(None);
+    }
+
+    let map = map.build();
+    match list_path.ok {
+        true => element_get(map, &list_path),
+        false => Ok(Some(map)),
+    }
+}
+
 fn array_bytes_get<'a>(
-    mut bytes: &'a [u8],
+    bytes: &'a [u8],
+    path: &Path<'a>,
+) -> Result<(Option<Element<'a>>, &'a [u8])> {
+    iter_bytes_get(element::ArrayIter::new(&bytes[1..]), path)
+}
+
+/// Gets from JSON Lines, each line is treated as an element of an array.
+fn lines_get<'a>(bytes: &'a [u8], path: &Path<'a>) -> Result<(Option<Element<'a>>, &'a [u8])> {
+    let iter = element::ArrayIter::new(bytes);
+    if !path.part.is_empty() && path.modifier.is_none() && !path.descent {
+        return iter_bytes_get(iter, path);
+    }
+
+    let lines = Element::List(iter.collect::<Result<_>>()?);
+    match path.part.is_empty() {
+        true => Ok((Some(lines), &[])),
+        false => Ok((element_get(lines, path)?, &[])),
+    }
+}
+
+fn iter_bytes_get<'a>(
+    mut iter: element::ArrayIter<'a>,
     path: &Path<'a>,
 ) -> Result<(Option<Element<'a>>, &'a [u8])> {
-    let mut index = 0;
-    let (idx, get_idx) = match str::from_utf8(path.part)
+    if let Some(slice) = &path.slice {
+        let elements = slice_array(&mut iter, slice)?;
+        let element = iter_get(&mut elements.into_iter().map(Ok), path)?;
+        return Ok((element, iter.bytes));
+    }
+
+    match str::from_utf8(path.part)
         .map_err(|_| Error::Path)?
-        .parse::<usize>()
+        .parse::<isize>()
     {
-        Ok(i) => (i, true),
-        Err(_) => (0, false),
-    };
+        Ok(idx) if idx >= 0 => {
+            for _ in 0..idx {
+                if iter.next().transpose()?.is_none() {
+                    return Ok((None, iter.bytes));
+                }
+            }
 
-    let query = path.borrow_query();
-    let query_key = query.get_path()?;
+            if !path.more {
+                return element::read_one(iter.bytes);
+            }
+
+            let next_path = path.parse_next()?;
+            let bytes = iter.bytes;
+            match bytes.iter().position(|b| !b" \t\r\n,".contains(b)) {
+                // step into the compound value without reading it first
+                Some(i) if bytes[i] == b'{' || bytes[i] == b'[' => {
+                    bytes_get(&bytes[i..], &next_path)
+                }
+                _ => match element::read_one(bytes)? {
+                    (Some(element), left) => Ok((element_get(element, &next_path)?, left)),
+                    (None, left) => Ok((None, left)),
+                },
+            }
+        }
+        Ok(idx) => {
+            // negative index, only keeps the last elements while reading
+            let size = idx.unsigned_abs();
+            let mut last = VecDeque::new();
+            for element in &mut iter {
+                if last.len() == size {
+                    last.pop_front();
+                }
+                last.push_back(element?);
+            }
+
+            let element = match last.len() == size {
+                true => last.pop_front(),
+                false => None,
+            };
+
+            match element {
+                Some(element) if path.more => {
+                    let next_path = path.parse_next()?;
+                    Ok((element_get(element, &next_path)?, iter.bytes))
+                }
+                element => Ok((element, iter.bytes)),
+            }
+        }
+        // a key never matches an array
+        Err(_) if !path.arrch => Ok((None, iter.bytes)),
+        Err(_) => {
+            let element = iter_get(&mut iter, path)?;
+            Ok((element, iter.bytes))
+        }
+    }
+}
 
+/// Takes the elements of the slice from an array. The elements are taken while
+/// reading if the bounds are known, otherwise the array is counted first.
+fn slice_array<'a>(iter: &mut element::ArrayIter<'a>, slice: &Slice) -> Result<Vec<Element<'a>>> {
     let mut elements = Vec::new();
-    let return_list = (query.on && query.all) || (!query.on && path.more);
-    let only_first = query.on && !query.all;
 
-    bytes = &bytes[1..];
+    if !slice.needs_len() {
+        let (start, take) = slice.bounds();
+        for (i, element) in iter.enumerate() {
+            if take.is_some_and(|take| i >= start + take) {
+                break;
+            }
 
-    let next_path = path.parse_next()?;
+            let element = element?;
+            if i >= start && (i - start) % slice.step as usize == 0 {
+                elements.push(element);
+            }
+        }
 
-    loop {
-        // index matched
-        if get_idx && idx == index {
-            return if path.more {
-                bytes_get(bytes, &next_path)
-            } else {
-                element::read_one(bytes)
+        return Ok(elements);
+    }
+
+    let len = element::ArrayIter::new(iter.bytes).count();
+    let indices = slice.indices(len);
+    let (lo, hi) = match (indices.iter().min(), indices.iter().max()) {
+        (Some(&lo), Some(&hi)) => (lo, hi),
+        _ => return Ok(elements),
+    };
+
+    for element in iter.skip(lo).take(hi - lo + 1) {
+        elements.push(element?);
+    }
+
+    Ok(pick(elements, lo, &indices))
+}
+
+/// Picks the elements by the indices, the first element is at `offset`.
+fn pick<'a>(elements: Vec<Element<'a>>, offset: usize, indices: &[usize]) -> Vec<Element<'a>> {
+    let mut elements: Vec<Option<Element>> = elements.into_iter().map(Some).collect();
+    indices
+        .iter()
+        .filter_map(|i| elements.get_mut(i - offset).and_then(Option::take))
+        .collect()
+}
+
+/// Gets from a `!` literal in the path.
+fn literal_get<'a>(literal: &'a [u8], path: &Path<'a>) -> Result<Option<Element<'a>>> {
+    match element::read_one(literal)? {
+        (Some(element), _) if path.more => element_get(element, &path.parse_next()?),
+        (element, _) => Ok(element),
+    }
+}
+
+/// Gets from the element and all the values nested in it, the path after `**`
+/// maps each of them like the path after `#`.
+fn descent_get<'a>(element: Element<'a>, path: &Path<'a>) -> Result<Option<Element<'a>>> {
+    let mut iter = Descendants {
+        stack: vec![vec![element].into_iter()],
+    };
+    iter_get(&mut iter, path)
+}
+
+/// Walks an element and the values nested in it, depth first.
+struct Descendants<'a> {
+    stack: Vec<vec::IntoIter<Element<'a>>>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = Result<Element<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = loop {
+            match self.stack.last_mut()?.next() {
+                Some(element) => break element,
+                None => self.stack.pop(),
             };
+        };
+
+        let children = match &element {
+            Element::Array(s) => element::array_elements(s),
+            Element::Object(s) => element::object_members(s)
+                .map(|members| members.into_iter().map(|(_, value)| value).collect()),
+            Element::List(elements) => Ok(elements.clone()),
+            Element::Map(m) => Ok(m.iter().map(|(_, value)| value.clone()).collect()),
+            _ => Ok(vec![]),
+        };
+
+        match children {
+            Ok(children) => self.stack.push(children.into_iter()),
+            Err(err) => return Some(Err(err)),
         }
 
-        let (readed, left) = element::read_one(bytes)?;
-        bytes = left;
+        Some(Ok(element))
+    }
+}
 
-        let mut element = match readed {
-            None => break,
-            Some(el) => el,
-        };
+/// Gets from the elements of an array or a list by `#`, `#(...)` or `#(...)#`.
+fn iter_get<'a, I>(iter: &mut I, path: &Path<'a>) -> Result<Option<Element<'a>>>
+where
+    I: Iterator<Item = Result<Element<'a>>>,
+{
+    let query = path.borrow_query();
+
+    let return_list =
+        path.slice.is_some() || path.descent || (query.on && query.all) || (!query.on && path.more);
+    let only_first = query.on && !query.all;
+
+    // the path after `#` maps each element until a `|`, and the rest is applied to the list
+    let (each_path, list_path) = match return_list && path.more {
+        true => path.parse_next_piped()?,
+        false => (path.parse_next()?, Path::default()),
+    };
+
+    let mut elements = Vec::new();
+    let mut count = 0;
+
+    for element in iter {
+        let mut element = element?;
 
         // do query filter
-        if query.on {
-            if query.has_path() {
-                match element_ref_get(&element, &query_key)? {
-                    None => continue,
-                    Some(v) => {
-                        if !query.match_element(&v) {
-                            continue;
-                        }
-                    }
-                }
-            } else if !query.match_element(&element) {
-                continue;
-            }
+        if query.on && !query.match_element(&element)? {
+            continue;
         }
 
-        index += 1;
+        count += 1;
 
-        if path.more {
-            match element_get(element, &next_path)? {
+        if each_path.ok {
+            match element_get(element, &each_path)? {
                 Some(el) => element = el,
                 None => continue,
             }
         }
 
         if only_first {
-            return Ok((Some(element), bytes));
+            return Ok(Some(element));
         }
 
         if return_list {
@@ -366,12 +646,14 @@ fn array_bytes_get<'a>(
     }
 
     if return_list {
-        Ok((Some(Element::List(elements)), bytes))
-    } else if only_first {
-        Ok((None, bytes))
-    } else if path.arrch {
-        Ok((Some(Element::Count(index)), bytes))
+        let list = Element::List(elements);
+        match list_path.ok {
+            true => element_get(list, &list_path),
+            false => Ok(Some(list)),
+        }
+    } else if path.arrch && !only_first {
+        Ok(Some(Element::Count(count)))
     } else {
-        Ok((None, bytes))
+        Ok(None)
     }
 }
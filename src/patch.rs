@@ -0,0 +1,261 @@
+//! Applies [JSON Patch](https://tools.ietf.org/html/rfc6902) documents.
+//!
+//! ```
+//! use ajson::Result;
+//! fn main() -> Result<()> {
+//!     let json = r#"{"name": "Tom", "tags": ["a"]}"#;
+//!     let patch = r#"[
+//!         {"op": "test", "path": "/name", "value": "Tom"},
+//!         {"op": "replace", "path": "/name", "value": "Jim"},
+//!         {"op": "add", "path": "/tags/-", "value": "b"},
+//!         {"op": "copy", "from": "/name", "path": "/nick"}
+//!     ]"#;
+//!
+//!     let json = ajson::patch::apply(json, patch)?;
+//!     assert_eq!(json, r#"{"name": "Jim", "tags": ["a","b"], "nick":"Jim"}"#);
+//!     Ok(())
+//! }
+//! ```
+
+use std::{borrow::Cow, ops::Range};
+
+use crate::{
+    edit::{self, Child},
+    element::{self, Element},
+    unescape::escape,
+    util, Error, Result,
+};
+
+/// Applies the operations of the patch to the JSON and returns the new
+/// JSON. The operations are applied all or nothing, if one of them fails
+/// the error is returned:
+///
+/// * [`Error::Patch`](../enum.Error.html) if the patch is malformed.
+/// * [`Error::Path`](../enum.Error.html) if a location doesn't exist, or
+///   can't be added to.
+/// * [`Error::Test`](../enum.Error.html) if a `test` operation fails.
+///
+/// The locations are [JSON Pointers](https://tools.ietf.org/html/rfc6901),
+/// not paths.
+/// ```
+/// let json = r#"{"version": 1}"#;
+/// let patch = r#"[{"op": "test", "path": "/version", "value": 2}]"#;
+/// assert_eq!(ajson::patch::apply(json, patch), Err(ajson::Error::Test));
+/// ```
+pub fn apply(json: &str, patch: &str) -> Result<String> {
+    let operations = operations(patch)?;
+
+    let mut json = Cow::Borrowed(json);
+    for operation in operations {
+        json = Cow::Owned(operation.apply(&json)?);
+    }
+    Ok(json.into_owned())
+}
+
+enum Operation<'a> {
+    Add(Vec<String>, &'a str),
+    Remove(Vec<String>),
+    Replace(Vec<String>, &'a str),
+    Move(Vec<String>, Vec<String>),
+    Copy(Vec<String>, Vec<String>),
+    Test(Vec<String>, &'a str),
+}
+
+impl<'a> Operation<'a> {
+    fn apply(&self, json: &str) -> Result<String> {
+        match self {
+            Operation::Add(path, value) => add(json, path, value),
+            Operation::Remove(path) => remove(json, path),
+            Operation::Replace(path, value) => {
+                let span = find(json, path)?.ok_or(Error::Path)?;
+                Ok(edit::splice(json, span, value))
+            }
+            Operation::Move(from, path) => {
+                if from == path {
+                    return find(json, from)?
+                        .map(|_| json.to_string())
+                        .ok_or(Error::Path);
+                }
+                // a value can't be moved into itself
+                if path.starts_with(from) {
+                    return Err(Error::Path);
+                }
+
+                let span = find(json, from)?.ok_or(Error::Path)?;
+                let value = json[span].to_string();
+                add(&remove(json, from)?, path, &value)
+            }
+            Operation::Copy(from, path) => {
+                let span = find(json, from)?.ok_or(Error::Path)?;
+                add(json, path, &json[span])
+            }
+            Operation::Test(path, value) => {
+                let span = find(json, path)?.ok_or(Error::Test)?;
+                let target = element::read_one(json[span].as_bytes())?.0;
+                let value = element::read_one(value.as_bytes())?.0;
+                match (target, value) {
+                    (Some(a), Some(b)) if element::equal(&a, &b)? => Ok(json.to_string()),
+                    _ => Err(Error::Test),
+                }
+            }
+        }
+    }
+}
+
+/// Parses the operations of the patch, a JSON array of objects.
+fn operations(patch: &str) -> Result<Vec<Operation<'_>>> {
+    let patch = util::trim_whitespace_u8(patch.as_bytes());
+    if patch.first() != Some(&b'[') {
+        return Err(Error::Patch);
+    }
+
+    let mut operations = Vec::new();
+    for element in element::array_elements(patch)? {
+        let members = match element {
+            Element::Object(s) => element::object_members(s)?,
+            _ => return Err(Error::Patch),
+        };
+
+        let member = |name: &str| {
+            members
+                .iter()
+                .find(|((key, _), _)| &key[1..key.len() - 1] == name.as_bytes())
+                .map(|(_, value)| value)
+        };
+        let string = |name: &str| match member(name) {
+            Some(value @ Element::String(..)) => Ok(value.to_value().to_string()),
+            _ => Err(Error::Patch),
+        };
+        let pointer = |name: &str| string(name).and_then(|s| parse_pointer(&s));
+        let value = || match member("value").and_then(|value| value.raw()) {
+            Some(raw) => Ok(unsafe { std::str::from_utf8_unchecked(raw) }),
+            None => Err(Error::Patch),
+        };
+
+        let operation = match string("op")?.as_str() {
+            "add" => Operation::Add(pointer("path")?, value()?),
+            "remove" => Operation::Remove(pointer("path")?),
+            "replace" => Operation::Replace(pointer("path")?, value()?),
+            "move" => Operation::Move(pointer("from")?, pointer("path")?),
+            "copy" => Operation::Copy(pointer("from")?, pointer("path")?),
+            "test" => Operation::Test(pointer("path")?, value()?),
+            _ => return Err(Error::Patch),
+        };
+        operations.push(operation);
+    }
+
+    Ok(operations)
+}
+
+/// Splits the JSON Pointer into the unescaped reference tokens.
+fn parse_pointer(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    if !pointer.starts_with('/') {
+        return Err(Error::Patch);
+    }
+
+    pointer[1..]
+        .split('/')
+        .map(|token| {
+            // `~` must be followed by `0` or `1`
+            let invalid = token
+                .match_indices('~')
+                .any(|(i, _)| !matches!(token.as_bytes().get(i + 1), Some(b'0') | Some(b'1')));
+            match invalid {
+                true => Err(Error::Patch),
+                false => Ok(token.replace("~1", "/").replace("~0", "~")),
+            }
+        })
+        .collect()
+}
+
+fn add(json: &str, path: &[String], value: &str) -> Result<String> {
+    let (token, parent) = match path.split_last() {
+        Some(split) => split,
+        None => return Ok(value.to_string()),
+    };
+
+    let span = find(json, parent)?.ok_or(Error::Path)?;
+    let bytes = json.as_bytes();
+    let children = edit::children(bytes, &bytes[span.clone()])?;
+
+    match bytes[span.start] {
+        b'{' => match child(&children, true, token) {
+            Some(i) => Ok(edit::splice(json, children[i].value_span(), value)),
+            None => {
+                let member = format!("{}:{}", escape(token), value);
+                edit::append(json, span, &[member])
+            }
+        },
+        b'[' => match token.as_str() {
+            "-" => edit::append(json, span, &[value.to_string()]),
+            token => match index(token) {
+                Some(i) if i < children.len() => {
+                    Ok(edit::insert_before(json, children[i].span.start, value))
+                }
+                Some(i) if i == children.len() => edit::append(json, span, &[value.to_string()]),
+                _ => Err(Error::Path),
+            },
+        },
+        _ => Err(Error::Path),
+    }
+}
+
+fn remove(json: &str, path: &[String]) -> Result<String> {
+    let (token, parent) = path.split_last().ok_or(Error::Path)?;
+    let span = find(json, parent)?.ok_or(Error::Path)?;
+    let bytes = json.as_bytes();
+    let children = edit::children(bytes, &bytes[span.clone()])?;
+
+    match child(&children, bytes[span.start] == b'{', token) {
+        Some(i) => edit::remove_children(json, span, &[i]),
+        None => Err(Error::Path),
+    }
+}
+
+/// Returns the span of the value at the pointer.
+fn find(json: &str, path: &[String]) -> Result<Option<Range<usize>>> {
+    let bytes = json.as_bytes();
+    let root = util::trim_whitespace_u8(bytes);
+    if root.is_empty() {
+        return Ok(None);
+    }
+
+    let start = edit::offset(bytes, root);
+    let mut span = start..start + root.len();
+    for token in path {
+        if !matches!(bytes[span.start], b'{' | b'[') {
+            return Ok(None);
+        }
+
+        let children = edit::children(bytes, &bytes[span.clone()])?;
+        match child(&children, bytes[span.start] == b'{', token) {
+            Some(i) => span = children[i].value_span(),
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(span))
+}
+
+/// Returns the index of the member with the key, or of the element at the
+/// index.
+fn child(children: &[Child], object: bool, token: &str) -> Option<usize> {
+    match object {
+        true => children.iter().rposition(|child| child.has_key(token)),
+        false => index(token).filter(|&i| i < children.len()),
+    }
+}
+
+/// Parses the array index, the leading zeros are not allowed.
+fn index(token: &str) -> Option<usize> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    match valid {
+        true => token.parse().ok(),
+        false => None,
+    }
+}
@@ -1,4 +1,5 @@
-use super::{query::Query, sub_selector::SubSelector, Path};
+use super::{modifier::Modifier, query::Query, slice::Slice, sub_selector::SubSelector, Path};
+use crate::Options;
 
 #[derive(Default)]
 pub struct Builder<'a> {
@@ -6,29 +7,43 @@ pub struct Builder<'a> {
     ident:     Option<&'a [u8]>,
     next:      Option<&'a [u8]>,
     more:      Option<bool>,
-    #[cfg(feature = "wild")]
     wild:      Option<bool>,
     arrch:     Option<bool>,
     query:     Option<Query<'a>>,
     selectors: Option<Vec<SubSelector<'a>>>,
     arrsel:    Option<bool>,
     esc:       Option<bool>,
+    modifier:  Option<Modifier<'a>>,
+    pipe:      Option<bool>,
+    slice:     Option<Slice>,
+    descent:   Option<bool>,
+    lines:     Option<bool>,
+    literal:   Option<&'a [u8]>,
+    fold:      Option<bool>,
+    opts:      Options<'a>,
 }
 
 impl<'a> Builder<'a> {
     pub fn build(self) -> crate::Result<Path<'a>> {
         Ok(Path {
-            ok: self.ok.unwrap_or_default(),
-            part: self.ident.unwrap_or_default(),
-            next: self.next.unwrap_or_default(),
-            query: self.query,
+            ok:        self.ok.unwrap_or_default(),
+            part:      self.ident.unwrap_or_default(),
+            next:      self.next.unwrap_or_default(),
+            query:     self.query,
             selectors: self.selectors,
-            arrsel: self.arrsel.unwrap_or_default(),
-            more: self.more.unwrap_or_default(),
-            #[cfg(feature = "wild")]
-            wild: self.wild.unwrap_or_default(),
-            arrch: self.arrch.unwrap_or_default(),
-            esc: self.esc.unwrap_or_default(),
+            arrsel:    self.arrsel.unwrap_or_default(),
+            more:      self.more.unwrap_or_default(),
+            wild:      self.wild.unwrap_or_default(),
+            arrch:     self.arrch.unwrap_or_default(),
+            esc:       self.esc.unwrap_or_default(),
+            modifier:  self.modifier,
+            pipe:      self.pipe.unwrap_or_default(),
+            slice:     self.slice,
+            descent:   self.descent.unwrap_or_default(),
+            lines:     self.lines.unwrap_or_default(),
+            literal:   self.literal,
+            fold:      self.fold.unwrap_or_default(),
+            opts:      self.opts,
         })
     }
 }
@@ -49,7 +64,6 @@ impl<'a> Builder<'a> {
         self
     }
 
-    #[cfg(feature = "wild")]
     pub fn wild(mut self, wild: bool) -> Self {
         self.wild = Some(wild);
         self
@@ -84,4 +98,44 @@ impl<'a> Builder<'a> {
         self.arrsel = Some(arrsel);
         self
     }
+
+    pub fn modifier(mut self, modifier: Modifier<'a>) -> Self {
+        self.modifier = Some(modifier);
+        self
+    }
+
+    pub fn pipe(mut self, pipe: bool) -> Self {
+        self.pipe = Some(pipe);
+        self
+    }
+
+    pub fn slice(mut self, slice: Slice) -> Self {
+        self.slice = Some(slice);
+        self
+    }
+
+    pub fn descent(mut self, descent: bool) -> Self {
+        self.descent = Some(descent);
+        self
+    }
+
+    pub fn lines(mut self, lines: bool) -> Self {
+        self.lines = Some(lines);
+        self
+    }
+
+    pub fn literal(mut self, literal: &'a [u8]) -> Self {
+        self.literal = Some(literal);
+        self
+    }
+
+    pub fn fold(mut self, fold: bool) -> Self {
+        self.fold = Some(fold);
+        self
+    }
+
+    pub fn opts(mut self, opts: Options<'a>) -> Self {
+        self.opts = opts;
+        self
+    }
 }
@@ -1,33 +1,20 @@
 mod builder;
 mod parser;
 
+mod modifier;
 mod query;
+mod slice;
 mod sub_selector;
 
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
 use builder::Builder;
+pub use modifier::Modifier;
+pub use slice::Slice;
 pub use sub_selector::SubSelector;
 
 use self::query::{Query, DEFAULT_NONE_QUERY};
-#[cfg(feature = "wild")]
-use crate::wild;
-use crate::{unescape, util, Result};
-
-pub const DEFAULT_NONE_PATH: Path = Path {
-    ok: false,
-    part: &[],
-    next: &[],
-    more: false,
-    #[cfg(feature = "wild")]
-    wild: false,
-    arrch: false,
-
-    query:     None,
-    selectors: None,
-    arrsel:    false,
-    esc:       false,
-};
+use crate::{unescape, util, wild, Options, Result};
 
 #[derive(Default)]
 pub struct Path<'a> {
@@ -38,10 +25,17 @@ pub struct Path<'a> {
     pub selectors: Option<Vec<SubSelector<'a>>>,
     pub arrsel:    bool,
     pub more:      bool,
-    #[cfg(feature = "wild")]
     pub wild:      bool,
     pub arrch:     bool,
     pub esc:       bool,
+    pub modifier:  Option<Modifier<'a>>,
+    pub pipe:      bool,
+    pub slice:     Option<Slice>,
+    pub descent:   bool,
+    pub lines:     bool,
+    pub literal:   Option<&'a [u8]>,
+    pub fold:      bool,
+    pub opts:      Options<'a>,
 }
 
 impl<'a> fmt::Debug for Path<'a> {
@@ -53,9 +47,12 @@ impl<'a> fmt::Debug for Path<'a> {
         })?;
 
         write!(f, " more={}", self.more)?;
-        #[cfg(feature = "wild")]
+        write!(f, " pipe={}", self.pipe)?;
         write!(f, " wild={}", self.wild)?;
         write!(f, " arrch={}", self.arrch)?;
+        write!(f, " descent={}", self.descent)?;
+        write!(f, " lines={}", self.lines)?;
+        write!(f, " fold={}", self.fold)?;
 
         if self.selectors.is_some() {
             for sel in self.borrow_selectors() {
@@ -65,13 +62,26 @@ impl<'a> fmt::Debug for Path<'a> {
         if self.has_query() {
             write!(f, " query={:?}", self.query)?;
         }
+        if let Some(modifier) = &self.modifier {
+            write!(f, " modifier={:?}", modifier)?;
+        }
+        if let Some(literal) = self.literal {
+            write!(f, " literal=`{}`", String::from_utf8_lossy(literal))?;
+        }
+        if let Some(slice) = &self.slice {
+            write!(f, " slice={:?}", slice)?;
+        }
         write!(f, ">")
     }
 }
 
 impl<'a> Path<'a> {
     pub fn from_slice(v: &'a [u8]) -> Result<Path<'a>> {
-        parser::parse(v)
+        parser::parse_root(v, Options::new())
+    }
+
+    pub fn from_slice_with(v: &'a [u8], opts: Options<'a>) -> Result<Path<'a>> {
+        parser::parse_root(v, opts)
     }
 
     pub fn builder<'b>() -> Builder<'b> {
@@ -79,10 +89,15 @@ impl<'a> Path<'a> {
     }
 
     pub fn is_match(&self, key: &[u8], key_esc: bool) -> bool {
-        #[cfg(feature = "wild")]
+        if self.fold || self.opts.case_insensitive {
+            return self.is_match_fold(key, key_esc);
+        }
+
         if self.wild {
-            return wild::is_match_u8(key, self.part);
-            return false;
+            return match key_esc {
+                true => wild::is_match_u8(unescape(key).as_bytes(), self.part),
+                false => wild::is_match_u8(key, self.part),
+            };
         }
 
         if key_esc {
@@ -95,14 +110,53 @@ impl<'a> Path<'a> {
         }
     }
 
+    fn is_match_fold(&self, key: &[u8], key_esc: bool) -> bool {
+        let key = match key_esc {
+            true => Cow::Owned(unescape(key)),
+            false => String::from_utf8_lossy(key),
+        };
+
+        if self.wild {
+            let part = util::fold(&String::from_utf8_lossy(self.part));
+            return wild::is_match_u8(util::fold(&key).as_bytes(), part.as_bytes());
+        }
+
+        match self.esc {
+            true => util::fold_eq(
+                &key,
+                &String::from_utf8_lossy(&util::strip_escapes(self.part)),
+            ),
+            false => util::fold_eq(&key, &String::from_utf8_lossy(self.part)),
+        }
+    }
+
     pub fn parse_next(&self) -> Result<Path<'a>> {
         if self.next.is_empty() {
             Ok(Path::default())
         } else {
-            Path::from_slice(self.next)
+            parser::parse(self.next, self.opts)
         }
     }
 
+    /// Splits the next path at the first `|`, returns the path applied to
+    /// each element of a result list and the path applied to the list.
+    pub fn parse_next_piped(&self) -> Result<(Path<'a>, Path<'a>)> {
+        if self.pipe {
+            return Ok((Path::default(), self.parse_next()?));
+        }
+
+        match parser::split_pipe(self.next) {
+            (each, Some(piped)) => Ok((self.parse_sub(each)?, self.parse_sub(piped)?)),
+            (each, None) => Ok((self.parse_sub(each)?, Path::default())),
+        }
+    }
+
+    /// Parses a path nested in this one, such as a selector, with the
+    /// same options.
+    pub fn parse_sub(&self, v: &'a [u8]) -> Result<Path<'a>> {
+        parser::parse(v, self.opts)
+    }
+
     pub fn has_query(&self) -> bool {
         self.query.is_some()
     }
@@ -114,6 +168,10 @@ impl<'a> Path<'a> {
         }
     }
 
+    pub fn has_modifier(&self) -> bool {
+        self.modifier.is_some()
+    }
+
     pub fn has_selectors(&self) -> bool {
         self.selectors.is_some()
     }
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use crate::{element, Result};
+
+pub struct Modifier<'a> {
+    pub name: &'a [u8],
+    pub args: Option<&'a [u8]>,
+}
+
+impl<'a> Modifier<'a> {
+    pub fn new(name: &'a [u8], args: Option<&'a [u8]>) -> Modifier<'a> {
+        Modifier { name, args }
+    }
+}
+
+impl<'a> fmt::Debug for Modifier<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<modifier")?;
+        write!(f, " name=`{}`", String::from_utf8_lossy(self.name))?;
+        if let Some(args) = self.args {
+            write!(f, " args=`{}`", String::from_utf8_lossy(args))?;
+        }
+        write!(f, ">")
+    }
+}
+
+/// Parses a `@name` or `@name:args` segment, the first byte must be `@`.
+/// Returns the modifier and the length of the bytes it takes.
+pub fn parse_modifier(v: &[u8]) -> Result<(Modifier<'_>, usize)> {
+    let mut i = 1;
+    while i < v.len() {
+        match v[i] {
+            b'.' | b'|' | b':' => break,
+            _ => i += 1,
+        }
+    }
+
+    let name = &v[1..i];
+    if i >= v.len() || v[i] != b':' {
+        return Ok((Modifier::new(name, None), i));
+    }
+
+    i += 1;
+    let start = i;
+    match v.get(i) {
+        Some(b'{') | Some(b'[') => {
+            let (s, _) = element::compound_u8(&v[i..])?;
+            i += s.len();
+        }
+        Some(b'"') => {
+            let (s, _, _) = element::string_u8(&v[i..])?;
+            i += s.len();
+        }
+        _ => {
+            while i < v.len() && v[i] != b'.' && v[i] != b'|' {
+                i += 1;
+            }
+        }
+    }
+
+    Ok((Modifier::new(name, Some(&v[start..i])), i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_modifier() -> Result<()> {
+        let (m, offset) = parse_modifier(b"@reverse")?;
+        assert_eq!(m.name, b"reverse");
+        assert_eq!(m.args, None);
+        assert_eq!(offset, 8);
+
+        let (m, offset) = parse_modifier(br#"@flatten:{"deep":true}.0"#)?;
+        assert_eq!(m.name, b"flatten");
+        assert_eq!(m.args, Some(&br#"{"deep":true}"#[..]));
+        assert_eq!(offset, 22);
+
+        let (m, offset) = parse_modifier(b"@this.name")?;
+        assert_eq!(m.name, b"this");
+        assert_eq!(offset, 5);
+
+        Ok(())
+    }
+}
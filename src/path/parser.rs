@@ -1,16 +1,60 @@
+use std::str;
+
 use super::{
-    query::{Query, QueryValue},
-    sub_selector, Path,
+    builder::Builder,
+    modifier,
+    query::{Comparison, Expr, Query, QueryValue},
+    slice, sub_selector, Path,
 };
-use crate::{element, number::Number, util, Result};
+use crate::{element, element::Element, modifiers, number::Number, util, Error, Options, Result};
+
+/// Parses the whole path, only a leading `..` reads the input as JSON
+/// Lines.
+pub(super) fn parse_root<'a>(v: &'a [u8], opts: Options<'a>) -> Result<Path<'a>> {
+    if !v.starts_with(b"..") {
+        return parse(v, opts);
+    }
+
+    let mut path = match v.len() {
+        2 => Path::builder().opts(opts).ok(true).build()?,
+        _ => parse(&v[2..], opts)?,
+    };
+    path.lines = true;
+    Ok(path)
+}
 
-pub(super) fn parse(v: &[u8]) -> Result<Path> {
+pub(super) fn parse<'a>(v: &'a [u8], opts: Options<'a>) -> Result<Path<'a>> {
     if v.is_empty() {
         return Ok(Default::default());
     }
 
+    // `!` followed by a JSON value is a literal, e.g. `!true`
+    if v[0] == b'!' {
+        if let Some(offset) = literal_len(&v[1..]).map(|n| n + 1) {
+            if matches!(v.get(offset), None | Some(b'.') | Some(b'|')) {
+                let current_path = Path::builder().opts(opts).literal(&v[1..offset]);
+                return segment(current_path, v, offset);
+            }
+        }
+    }
+
+    if v[0] == b'@' {
+        let (modifier, offset) = modifier::parse_modifier(v)?;
+        // unknown modifiers fall back to plain keys, e.g. `@timestamp`
+        if modifiers::exists(modifier.name, opts) {
+            let current_path = Path::builder().opts(opts).modifier(modifier);
+            return segment(current_path, v, offset);
+        }
+    }
+
+    // `**` walks all the nested values
+    if v.starts_with(b"**") && matches!(v.get(2), None | Some(b'.') | Some(b'|')) {
+        let current_path = Path::builder().opts(opts).descent(true);
+        return segment(current_path, v, 2);
+    }
+
     let bytes = v;
-    let mut current_path = Path::builder();
+    let mut current_path = Path::builder().opts(opts);
     let mut depth = 0;
     let mut i = 0;
     let mut arrch = false;
@@ -24,44 +68,41 @@ pub(super) fn parse(v: &[u8]) -> Result<Path> {
                 current_path = current_path.esc(true);
                 continue;
             }
-            b']' | b')' | b'}' => {
-                if depth > 0 {
-                    depth -= 0;
-                }
+            b']' | b')' | b'}' if depth > 0 => {
+                depth -= 0;
             }
-            b'.' => {
-                if depth == 0 && i > 0 {
-                    current_path = current_path.ident(&v[..i]);
-                    current_path = current_path.ok(true);
-                    current_path = current_path.more(true);
-                    i += 1;
+            b'.' | b'|' if depth == 0 && i > 0 => {
+                current_path = key(current_path, &v[..i]);
+                current_path = current_path.ok(true);
+                current_path = current_path.more(true);
+                current_path = current_path.pipe(b == b'|');
+                i += 1;
 
-                    current_path = current_path.next(&v[i..]);
+                current_path = current_path.next(&v[i..]);
 
-                    return current_path.build();
-                }
+                return current_path.build();
             }
-            #[cfg(feature = "wild")]
             b'*' | b'?' => current_path = current_path.wild(true),
-            b'#' => {
-                if depth == 0 {
-                    current_path = current_path.arrch(true);
-                    arrch = true;
-                }
+            b'#' if depth == 0 => {
+                current_path = current_path.arrch(true);
+                arrch = true;
             }
             b @ b'[' | b @ b'(' | b @ b'{' => {
                 depth += 1;
                 if depth == 1 {
                     if arrch {
-                        i += 1;
-                        let (query, offset) = parse_query(&v[i..])?;
-                        if query.on {
-                            i += offset - 1;
-                        }
+                        let (query, offset) = parse_query(&v[i + 1..], opts)?;
                         current_path = current_path.query(query);
-
+                        i += offset + 1;
+                        // the query ends the segment, e.g. not `#(a>1))`
+                        if !matches!(v.get(i + 1), None | Some(b'.') | Some(b'|')) {
+                            return Err(Error::Path);
+                        }
+                        depth = 0;
+                    } else if let Some((slice, offset)) = slice::parse_slice(&v[i..])? {
+                        current_path = current_path.slice(slice);
+                        i += offset - 1;
                         depth = 0;
-                        continue;
                     } else {
                         let (selectors, offset, ok) = sub_selector::parse_selectors(&v[i..]);
                         if ok {
@@ -80,90 +121,368 @@ pub(super) fn parse(v: &[u8]) -> Result<Path> {
         i += 1;
     }
 
-    current_path.ident(v).more(false).ok(true).build()
+    key(current_path, v).more(false).ok(true).build()
+}
+
+/// Sets the key of the segment, a trailing `~` makes it case-insensitive.
+fn key<'a>(current_path: Builder<'a>, part: &'a [u8]) -> Builder<'a> {
+    match part {
+        [.., b, b'~'] if *b != b'\\' => current_path.ident(&part[..part.len() - 1]).fold(true),
+        _ => current_path.ident(part),
+    }
+}
+
+/// Returns the length of the JSON value at the start of the path.
+fn literal_len(v: &[u8]) -> Option<usize> {
+    // the reader skips the bytes before the value, e.g. the `x` of `!x1`
+    if !element::starts_value(*v.first()?) {
+        return None;
+    }
+
+    let (element, left) = element::read_one(v).ok()?;
+    let element = element?;
+    let len = v.len() - left.len();
+    if element.raw()?.len() != len {
+        return None;
+    }
+    match element {
+        // the reader doesn't check the keywords
+        Element::Boolean(_) | Element::Null(_) => {
+            let keyword = &v[..len];
+            (keyword == b"true" || keyword == b"false" || keyword == b"null").then_some(len)
+        }
+        _ => Some(len),
+    }
+}
+
+/// Builds a segment which takes the first `offset` bytes of the path.
+fn segment<'a>(current_path: Builder<'a>, v: &'a [u8], offset: usize) -> Result<Path<'a>> {
+    let current_path = current_path.ident(&v[..offset]);
+    match v.get(offset) {
+        Some(&b) if b == b'.' || b == b'|' => current_path
+            .more(true)
+            .pipe(b == b'|')
+            .next(&v[offset + 1..]),
+        _ => current_path.more(false),
+    }
+    .ok(true)
+    .build()
+}
+
+/// Splits the path at the first `|` which is neither escaped nor nested.
+pub(super) fn split_pipe(v: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < v.len() {
+        match v[i] {
+            b'\\' => i += 1,
+            b'"' => {
+                let (s, _, _) = element::string_u8(&v[i..]).unwrap_or_default();
+                i += s.len().max(1);
+                continue;
+            }
+            b'[' | b'(' | b'{' => depth += 1,
+            b']' | b')' | b'}' if depth > 0 => depth -= 1,
+            b'|' if depth == 0 => return (&v[..i], Some(&v[i + 1..])),
+            _ => (),
+        }
+        i += 1;
+    }
+
+    (v, None)
 }
 
-fn parse_query(v: &[u8]) -> Result<(Query, usize)> {
+/// Parses the query after `#(`, returns the query and the offset of the
+/// closing `)`, or of the `#` after it.
+fn parse_query<'a>(v: &'a [u8], opts: Options<'a>) -> Result<(Query<'a>, usize)> {
     if v.is_empty() {
         return Ok((Query::empty(), 0));
     }
 
-    let bytes = v;
     let mut q = Query::empty();
-    let mut depth = 1;
-    let mut end = 0;
+    let end = scan(v, 0, |_, depth| depth < 0).ok_or(Error::Path)?;
 
-    let mut op_exist = false;
-    let mut op_start = 0;
-    let mut op_end = 0;
+    let mut offset = end;
+    if v.get(end + 1) == Some(&b'#') {
+        q.set_all(true);
+        offset += 1;
+    }
 
-    let mut i = 0;
+    let mut parser = ExprParser {
+        v: &v[..end],
+        i: 0,
+        opts,
+    };
+    if let Some(expr) = parser.parse()? {
+        q.set_expr(expr);
+    }
+    q.set_on(true);
 
-    while i < bytes.len() {
-        let &b = unsafe { bytes.get_unchecked(i) };
+    Ok((q, offset))
+}
 
-        match b {
-            b'!' | b'=' | b'<' | b'>' | b'%' => {
-                if depth == 1 {
-                    if !op_exist {
-                        op_exist = true;
-                        op_start = i;
-                        op_end = op_start;
-                    } else {
-                        op_end += 1;
-                    }
-                }
+/// Scans the path from `i`, skipping the escaped bytes, the strings and the
+/// regex literals, and returns the first offset where `stop` is true for the
+/// byte and the depth.
+fn scan(v: &[u8], mut i: usize, stop: impl Fn(&[u8], i32) -> bool) -> Option<usize> {
+    let mut depth = 0;
+    let mut prev = 0;
+    while i < v.len() {
+        match v[i] {
+            b'\\' => {
+                i += 2;
+                continue;
             }
-            b'[' | b'(' => {
-                depth += 1;
+            b'"' => {
+                let (s, _, _) = element::string_u8(&v[i..]).unwrap_or_default();
+                i += s.len().max(1);
+                prev = b'"';
+                continue;
             }
-            b']' | b')' => {
-                depth -= 1;
-                if depth == 0 {
-                    match bytes.get(i + 1) {
-                        Some(b'#') => {
-                            q.set_all(true);
-                            end = i;
-                            i += 1;
-                        }
-                        Some(_) => end = i - 1,
-                        None => end = i,
-                    }
-                    break;
+            b'/' if prev == b'~' => {
+                i += regex_literal(&v[i..]).map_or(1, |(s, _)| s.len());
+                prev = b'/';
+                continue;
+            }
+            b'[' | b'(' | b'{' => depth += 1,
+            b']' | b')' | b'}' => depth -= 1,
+            _ => (),
+        }
+
+        if stop(&v[i..], depth) {
+            return Some(i);
+        }
+        if v[i] != b' ' {
+            prev = v[i];
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Reads a `/pattern/flags` regex literal, the first byte must be `/`.
+/// Returns the whole literal and the pattern.
+fn regex_literal(v: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut i = 1;
+    while i < v.len() {
+        match v[i] {
+            b'\\' => i += 2,
+            b'/' => break,
+            _ => i += 1,
+        }
+    }
+
+    if i >= v.len() {
+        return None;
+    }
+
+    let end = i
+        + 1
+        + v[i + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_alphabetic())
+            .count();
+    Some((&v[..end], &v[1..i]))
+}
+
+/// Compiles the value of `=~` and `!~`, either a `/pattern/flags` literal
+/// or a string.
+#[cfg(feature = "regex")]
+fn regex_value(v: &[u8]) -> Result<QueryValue<'_>> {
+    let (pattern, flags) = match v.first() {
+        Some(b'/') => {
+            let (literal, pattern) = regex_literal(v).ok_or(Error::Path)?;
+            let flags = &literal[pattern.len() + 2..];
+            (String::from_utf8_lossy(pattern).replace("\\/", "/"), flags)
+        }
+        Some(b'"') => {
+            let (s, _, esc) = element::string_u8(v)?;
+            let s = &s[1..s.len() - 1];
+            let pattern = match esc {
+                true => crate::unescape(s),
+                false => String::from_utf8_lossy(s).into_owned(),
+            };
+            (pattern, &[][..])
+        }
+        _ => return Err(Error::Path),
+    };
+
+    let mut builder = regex::RegexBuilder::new(&pattern);
+    for flag in flags {
+        match flag {
+            b'i' => builder.case_insensitive(true),
+            b'm' => builder.multi_line(true),
+            b's' => builder.dot_matches_new_line(true),
+            b'x' => builder.ignore_whitespace(true),
+            _ => return Err(Error::Path),
+        };
+    }
+
+    let regex = builder.build().map_err(|_| Error::Path)?;
+    Ok(QueryValue::Regex(regex))
+}
+
+#[cfg(not(feature = "regex"))]
+fn regex_value(_: &[u8]) -> Result<QueryValue<'_>> {
+    Err(Error::Path)
+}
+
+const OP_BYTES: &[u8] = b"!=<>%~";
+
+/// Returns the `in` or `!in` operator at the start of the path.
+fn word_op(v: &[u8]) -> Option<&'static str> {
+    ["in", "!in"].iter().copied().find(|op| {
+        v.starts_with(op.as_bytes()) && matches!(v.get(op.len()), Some(b' ') | Some(b'['))
+    })
+}
+
+/// Parses the condition of a query:
+///
+/// ```text
+/// or         = and ("||" and)*
+/// and        = unary ("&&" unary)*
+/// unary      = "!" unary | "(" or ")" | comparison
+/// comparison = [path] [op value]
+/// ```
+struct ExprParser<'a> {
+    v:    &'a [u8],
+    i:    usize,
+    opts: Options<'a>,
+}
+
+impl<'a> ExprParser<'a> {
+    fn parse(&mut self) -> Result<Option<Expr<'a>>> {
+        if util::trim_space_u8(self.v).is_empty() {
+            return Ok(None);
+        }
+
+        let expr = self.or()?;
+        self.skip_space();
+        if self.i < self.v.len() {
+            return Err(Error::Path);
+        }
+        Ok(Some(expr))
+    }
+
+    fn skip_space(&mut self) {
+        while self.v.get(self.i) == Some(&b' ') {
+            self.i += 1;
+        }
+    }
+
+    fn eat(&mut self, token: &[u8]) -> bool {
+        self.skip_space();
+        if self.v[self.i..].starts_with(token) {
+            self.i += token.len();
+            return true;
+        }
+        false
+    }
+
+    fn or(&mut self) -> Result<Expr<'a>> {
+        let mut expr = self.and()?;
+        while self.eat(b"||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr<'a>> {
+        let mut expr = self.unary()?;
+        while self.eat(b"&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr<'a>> {
+        self.skip_space();
+        match self.v.get(self.i) {
+            // but not `!=` or `!%`
+            Some(b'!') if !self.v.get(self.i + 1).is_some_and(|b| OP_BYTES.contains(b)) => {
+                self.i += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some(b'(') => {
+                self.i += 1;
+                let expr = self.or()?;
+                if !self.eat(b")") {
+                    return Err(Error::Path);
                 }
+                Ok(expr)
             }
-            b' ' => (),
-            _ => {
-                if op_exist {
-                    let (val, offset) = parser_query_value(&v[i..])?;
-                    if val.exists() {
-                        q.set_val(val);
-                    }
-                    if offset > 1 {
-                        i += offset - 1;
-                    }
+            _ => self.comparison(),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr<'a>> {
+        let start = self.i;
+        let end = scan(self.v, start, |v, depth| {
+            depth < 0 || (depth == 0 && (v.starts_with(b"&&") || v.starts_with(b"||")))
+        })
+        .unwrap_or(self.v.len());
+        self.i = end;
+
+        let v = &self.v[start..end];
+        let op_start = scan(v, 0, |rest, depth| {
+            let i = v.len() - rest.len();
+            let word = match i {
+                // without a key, `in` tests the element itself, e.g. `#(in ["a"])`,
+                // but `#(in == 2)` reads the key `in`
+                0 => word_op(rest)
+                    .is_some_and(|op| util::trim_space_u8(&rest[op.len()..]).starts_with(b"[")),
+                _ => v[i - 1] == b' ' && word_op(rest).is_some(),
+            };
+            depth == 0 && (word || OP_BYTES.contains(&rest[0]))
+        });
+        let op_start = match op_start {
+            Some(op_start) => op_start,
+            None => {
+                // an empty operand, e.g. `#(&&)` or `#(!)`
+                let v = util::trim_space_u8(v);
+                if v.is_empty() {
+                    return Err(Error::Path);
                 }
+                let c = Comparison::new(v, self.opts)?;
+                return Ok(Expr::Comparison(c));
             }
         };
 
-        i += 1;
-    }
+        let op_end = match word_op(&v[op_start..]) {
+            Some(op) => op_start + op.len(),
+            None => v[op_start..]
+                .iter()
+                .position(|b| !OP_BYTES.contains(b))
+                .map_or(v.len(), |n| op_start + n),
+        };
 
-    q.set_on(true);
+        let mut c = Comparison::new(util::trim_space_u8(&v[..op_start]), self.opts)?;
+        let op = unsafe { str::from_utf8_unchecked(&v[op_start..op_end]) };
+        c.set_op(op);
+
+        let v = util::trim_space_u8(&v[op_end..]);
+        if v.is_empty() {
+            return Err(Error::Path);
+        }
+        let val = match op {
+            "=~" | "!~" => regex_value(v)?,
+            "in" | "!in" => match parser_query_value(v, self.opts)?.0 {
+                val @ QueryValue::Array(_) => val,
+                _ => return Err(Error::Path),
+            },
+            _ => parser_query_value(v, self.opts)?.0,
+        };
+        if val.exists() {
+            c.set_val(val);
+        }
 
-    if op_exist {
-        q.set_path(util::trim_space_u8(&v[..op_start]));
-        q.set_op(unsafe { std::str::from_utf8_unchecked(v.get_unchecked(op_start..op_end + 1)) });
-    } else if end > 0 {
-        q.set_path(util::trim_space_u8(&v[..end + 1]));
-    } else {
-        q.set_path(util::trim_space_u8(v));
+        Ok(Expr::Comparison(c))
     }
-
-    Ok((q, i))
 }
 
-fn parser_query_value(bytes: &[u8]) -> Result<(QueryValue, usize)> {
+fn parser_query_value<'a>(bytes: &'a [u8], opts: Options<'a>) -> Result<(QueryValue<'a>, usize)> {
     if let Some(b) = bytes.first() {
         let val = match b {
             b't' => {
@@ -179,13 +498,28 @@ fn parser_query_value(bytes: &[u8]) -> Result<(QueryValue, usize)> {
                 (QueryValue::Null, 4)
             }
             b'"' => {
-                let (s, _, _esc) = element::string_u8(bytes)?;
+                let (s, _, esc) = element::string_u8(bytes)?;
                 if s.len() < 2 {
                     (QueryValue::NotExist, s.len())
                 } else {
-                    (QueryValue::String(&s[1..s.len() - 1]), s.len())
+                    (Element::String(s, esc).to_value().into(), s.len())
                 }
             }
+            b'@' | b'$' if bytes.get(1) == Some(&b'.') => {
+                let path = Box::new(parse(&bytes[2..], opts)?);
+                match b {
+                    b'@' => (QueryValue::Current(path), bytes.len()),
+                    _ => (QueryValue::Root(path), bytes.len()),
+                }
+            }
+            b'[' => {
+                let (s, _) = element::compound_u8(bytes)?;
+                let items = element::array_elements(s)?
+                    .into_iter()
+                    .map(|el| el.to_value().into())
+                    .collect();
+                (QueryValue::Array(items), s.len())
+            }
             b'0'..=b'9' | b'-' => {
                 let (n, _) = element::number_u8(bytes)?;
                 (QueryValue::F64(Number::from(n).to_f64()), n.len())
@@ -206,81 +540,128 @@ mod tests {
 
     #[test]
     fn test_invalid_path() {
-        parse("friends.{}first]".as_bytes()).unwrap();
+        parse("friends.{}first]".as_bytes(), Options::new()).unwrap();
     }
 
     #[test]
     fn test_fn_parse_from_utf8() {
         let v = r#"name"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"#(last=="Murphy")#.first"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"friends.#(first!%"D*")#.last"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"c?ildren.0"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"#(sub_item>7)#.title"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"friends.#(nets."#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"friends.#()#"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = "widget.[window,name].#.name".as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"widget.menu.#(title="help")#.title"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
     }
 
     #[test]
     fn test_fn_parse() {
         let v = r#"name"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"#(last=="Murphy")#.first"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"friends.#(first!%"D*")#.last"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"c?ildren.0"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
 
         let v = r#"#(sub_item>7)#.title"#.as_bytes();
-        let p = parse(v);
+        let p = parse(v, Options::new());
+    }
+
+    #[test]
+    fn test_split_pipe() {
+        assert_eq!(split_pipe(b"a.b|c.d"), (&b"a.b"[..], Some(&b"c.d"[..])));
+        assert_eq!(split_pipe(b"a.b"), (&b"a.b"[..], None));
+        assert_eq!(split_pipe(b"a\\|b"), (&b"a\\|b"[..], None));
+        assert_eq!(
+            split_pipe(br#"#(a=="|")#|#"#),
+            (&br#"#(a=="|")#"#[..], Some(&b"#"[..]))
+        );
+    }
+
+    #[test]
+    fn test_regex_literal() {
+        assert_eq!(
+            regex_literal(br"/a\/b)/i)"),
+            Some((&br"/a\/b)/i"[..], &br"a\/b)"[..]))
+        );
+        assert_eq!(regex_literal(b"/ab"), None);
+        assert_eq!(scan(br#"a=~/)/ && b)"#, 0, |_, depth| depth < 0), Some(11));
+    }
+
+    #[test]
+    fn test_parse_query_expr() -> Result<()> {
+        let (q, offset) = parse_query(br#"a>1 && (b=="x)" || !c) && d)#.e"#, Options::new())?;
+        assert!(q.all);
+        assert_eq!(offset, 28);
+        match q.expr {
+            Some(Expr::And(a, b)) => {
+                assert!(matches!(*a, Expr::And(_, _)));
+                assert!(matches!(*b, Expr::Comparison(_)));
+            }
+            expr => panic!("unexpected {:?}", expr),
+        }
+
+        let (q, _) = parse_query(br#"!="x")"#, Options::new())?;
+        match q.expr {
+            Some(Expr::Comparison(c)) => assert_eq!(c.op, Some("!=")),
+            expr => panic!("unexpected {:?}", expr),
+        }
+
+        let (q, offset) = parse_query(b")", Options::new())?;
+        assert!(q.expr.is_none());
+        assert_eq!(offset, 0);
+
+        Ok(())
     }
 
     #[test]
     fn test_fn_parse_query() {
         let v = "first)".as_bytes();
-        let q = parse_query(v);
+        let q = parse_query(v, Options::new());
 
         let v = "first)#".as_bytes();
-        let q = parse_query(v);
+        let q = parse_query(v, Options::new());
 
         let v = r#"first="name")"#.as_bytes();
-        let q = parse_query(v);
+        let q = parse_query(v, Options::new());
 
         let v = r#"nets.#(=="ig"))"#.as_bytes();
-        let q = parse_query(v);
+        let q = parse_query(v, Options::new());
 
         let v = r#"nets.#(=="ig"))#"#.as_bytes();
-        let q = parse_query(v);
+        let q = parse_query(v, Options::new());
 
         let v = r#"=="ig")"#.as_bytes();
-        let q = parse_query(v);
+        let q = parse_query(v, Options::new());
 
         let v = r#"first=)"#.as_bytes();
-        let q = parse_query(v);
+        let q = parse_query(v, Options::new());
 
         let v = r#"sub_item>7)#.title"#.as_bytes();
-        let q = parse_query(v);
+        let q = parse_query(v, Options::new());
     }
 }
@@ -1,41 +1,73 @@
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
-use super::{parser, Path, DEFAULT_NONE_PATH};
-#[cfg(feature = "wild")]
-use crate::wild;
-use crate::{element::Element, Result, Value};
+use super::{parser, Path};
+use crate::{element::Element, util, wild, Options, Result, Value};
 
 pub const DEFAULT_NONE_QUERY: Query = Query {
-    on:    false,
-    path:  &[],
-    key:   None,
-    op:    None,
-    value: None,
-    all:   false,
+    on:   false,
+    expr: None,
+    all:  false,
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub enum QueryValue<'a> {
-    String(&'a [u8]),
+    String(Cow<'a, str>),
     F64(f64),
     Boolean(bool),
     Null,
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+    Array(Vec<QueryValue<'a>>),
+    /// A path relative to the current element, `@.path`.
+    Current(Box<Path<'a>>),
+    /// A path relative to the root of the document, `$.path`.
+    Root(Box<Path<'a>>),
     NotExist,
 }
 
 impl<'a> QueryValue<'a> {
     pub fn exists(&self) -> bool {
-        *self != QueryValue::NotExist
+        !matches!(self, QueryValue::NotExist)
+    }
+}
+
+impl<'a> From<Value<'a>> for QueryValue<'a> {
+    fn from(v: Value<'a>) -> QueryValue<'a> {
+        match v {
+            Value::String(s) => QueryValue::String(s),
+            Value::Number(n) => QueryValue::F64(n.to_f64()),
+            Value::Usize(n) => QueryValue::F64(n as f64),
+            Value::Boolean(b) => QueryValue::Boolean(b),
+            Value::Null => QueryValue::Null,
+            _ => QueryValue::NotExist,
+        }
+    }
+}
+
+/// The condition of a query, comparisons joined by `&&`, `||` and `!`.
+#[derive(Debug)]
+pub enum Expr<'a> {
+    Comparison(Comparison<'a>),
+    And(Box<Expr<'a>>, Box<Expr<'a>>),
+    Or(Box<Expr<'a>>, Box<Expr<'a>>),
+    Not(Box<Expr<'a>>),
+}
+
+impl<'a> Expr<'a> {
+    pub fn match_element(&self, element: &Element<'a>) -> Result<bool> {
+        match self {
+            Expr::Comparison(c) => c.match_element(element),
+            Expr::And(a, b) => Ok(a.match_element(element)? && b.match_element(element)?),
+            Expr::Or(a, b) => Ok(a.match_element(element)? || b.match_element(element)?),
+            Expr::Not(e) => Ok(!e.match_element(element)?),
+        }
     }
 }
 
 pub struct Query<'a> {
-    pub on:    bool,
-    pub path:  &'a [u8],
-    pub key:   Option<Box<Path<'a>>>,
-    pub op:    Option<&'a str>,
-    pub value: Option<QueryValue<'a>>,
-    pub all:   bool,
+    pub on:   bool,
+    pub expr: Option<Expr<'a>>,
+    pub all:  bool,
 }
 
 impl<'a> fmt::Debug for Query<'a> {
@@ -43,19 +75,8 @@ impl<'a> fmt::Debug for Query<'a> {
         write!(f, "<Query")?;
         write!(f, " on={}", self.on)?;
         write!(f, " all={}", self.all)?;
-        if !self.path.is_empty() {
-            write!(f, " path=`{}`", unsafe {
-                std::str::from_utf8_unchecked(self.path)
-            })?;
-        }
-        if self.key.is_some() {
-            write!(f, " key=`{:?}`", self.key.as_ref().unwrap())?;
-        }
-        if self.op.is_some() {
-            write!(f, " op=`{}`", self.op.as_ref().unwrap())?;
-        }
-        if self.value.is_some() {
-            write!(f, " value=`{:?}`", self.value.as_ref().unwrap())?;
+        if let Some(expr) = &self.expr {
+            write!(f, " expr={:?}", expr)?;
         }
         write!(f, ">")
     }
@@ -64,39 +85,73 @@ impl<'a> fmt::Debug for Query<'a> {
 impl<'a> Query<'a> {
     pub fn empty() -> Query<'a> {
         Query {
-            on:    false,
-            path:  &[],
-            key:   None,
-            op:    None,
-            value: None,
-            all:   false,
+            on:   false,
+            expr: None,
+            all:  false,
         }
     }
 
-    pub fn has_path(&self) -> bool {
-        !self.path.is_empty()
+    pub fn set_expr(&mut self, expr: Expr<'a>) {
+        self.expr = Some(expr);
     }
 
-    pub fn get_path(&self) -> Result<Path> {
-        match self.has_path() {
-            true => parser::parse(self.path),
-            false => Ok(Path::default()),
-        }
+    pub fn set_all(&mut self, all: bool) {
+        self.all = all;
     }
 
-    pub fn has_key(&self) -> bool {
-        self.key.is_some()
+    pub fn set_on(&mut self, on: bool) {
+        self.on = on;
     }
 
-    pub fn get_key(&self) -> &Path {
-        match self.key {
-            Some(_) => self.key.as_ref().unwrap(),
-            None => &DEFAULT_NONE_PATH,
+    /// Tests the element against the condition, a query without
+    /// condition matches all the elements.
+    pub fn match_element(&self, element: &Element<'a>) -> Result<bool> {
+        match &self.expr {
+            Some(expr) => expr.match_element(element),
+            None => Ok(true),
         }
     }
+}
 
-    pub fn set_path(&mut self, v: &'a [u8]) {
-        self.path = v;
+/// A single comparison such as `age>40`, the path is optional.
+pub struct Comparison<'a> {
+    pub path:  &'a [u8],
+    pub key:   Option<Box<Path<'a>>>,
+    pub op:    Option<&'a str>,
+    pub value: Option<QueryValue<'a>>,
+    pub fold:  bool,
+}
+
+impl<'a> fmt::Debug for Comparison<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<Comparison")?;
+        if !self.path.is_empty() {
+            write!(f, " path=`{}`", String::from_utf8_lossy(self.path))?;
+        }
+        if let Some(op) = &self.op {
+            write!(f, " op=`{}`", op)?;
+        }
+        if let Some(value) = &self.value {
+            write!(f, " value=`{:?}`", value)?;
+        }
+        write!(f, ">")
+    }
+}
+
+impl<'a> Comparison<'a> {
+    pub fn new(path: &'a [u8], opts: Options<'a>) -> Result<Comparison<'a>> {
+        let key = match path.is_empty() {
+            true => None,
+            false => Some(Box::new(parser::parse(path, opts)?)),
+        };
+
+        Ok(Comparison {
+            path,
+            key,
+            op: None,
+            value: None,
+            fold: opts.case_insensitive,
+        })
     }
 
     pub fn set_op(&mut self, op: &'a str) {
@@ -107,83 +162,119 @@ impl<'a> Query<'a> {
         self.value = Some(val);
     }
 
-    pub fn set_all(&mut self, all: bool) {
-        self.all = all;
-    }
-
-    pub fn set_key(&mut self, key: Path<'a>) {
-        if key.ok {
-            self.key = Some(Box::new(key));
-        }
-    }
+    /// Gets the value at the path of the element and compares it, the
+    /// comparison fails if the path doesn't exist.
+    pub fn match_element(&self, element: &Element<'a>) -> Result<bool> {
+        let v = match &self.key {
+            Some(key) => match crate::parser::element_ref_get(element, key)? {
+                Some(v) => v.to_value(),
+                None => return Ok(false),
+            },
+            None => element.to_value(),
+        };
 
-    pub fn set_on(&mut self, on: bool) {
-        self.on = on;
-    }
+        let (op, target) = match (self.op, &self.value) {
+            (Some(op), Some(target)) => (op, target),
+            _ => return Ok(true),
+        };
 
-    pub fn match_element(&self, element: &Element) -> bool {
-        if self.value.is_none() {
-            return true;
-        }
+        // the case-insensitive variants
+        let (op, fold) = match op {
+            "~=" => ("==", true),
+            "!~=" => ("!=", true),
+            "~%" => ("%", true),
+            "!~%" => ("!%", true),
+            op => (op, self.fold),
+        };
 
-        let op = match &self.op {
-            Some(ref s) => s,
-            None => return true,
+        // resolve the path on the right side
+        let resolved = match target {
+            QueryValue::Current(path) => crate::parser::element_ref_get(element, path)?,
+            QueryValue::Root(path) => {
+                let root = path.opts.root.unwrap_or_default();
+                crate::parser::bytes_get(root, path)?.0
+            }
+            _ => return Ok(compare(&v, op, target, fold)),
         };
 
-        let target = self.value.as_ref().unwrap();
-        let v = element.to_value();
-
-        match *target {
-            QueryValue::String(q) => match v {
-                Value::String(ref s) => match *op {
-                    "==" => s.as_bytes() == q,
-                    "=" => s.as_bytes() == q,
-                    "!=" => s.as_bytes() != q,
-                    ">" => s.as_bytes() > q,
-                    ">=" => s.as_bytes() >= q,
-                    "<" => s.as_bytes() < q,
-                    "<=" => s.as_bytes() <= q,
-                    #[cfg(feature = "wild")]
-                    "%" => wild::is_match_u8(s.as_bytes(), q),
-                    #[cfg(feature = "wild")]
-                    "!%" => !wild::is_match_u8(s.as_bytes(), q),
-                    _ => false,
-                },
+        match resolved {
+            Some(r) => Ok(compare(&v, op, &QueryValue::from(r.to_value()), fold)),
+            None => Ok(false),
+        }
+    }
+}
+
+/// Compares the value with the target of the query by the operator, the
+/// strings are compared case-insensitively if `fold` is true.
+fn compare(v: &Value, op: &str, target: &QueryValue, fold: bool) -> bool {
+    match *target {
+        QueryValue::String(ref q) => match *v {
+            Value::String(ref s) if fold && matches!(op, "==" | "=" | "!=" | "%" | "!%") => {
+                let s = Value::String(util::fold(s).into());
+                let q = QueryValue::String(util::fold(q).into());
+                compare(&s, op, &q, false)
+            }
+            Value::String(ref s) => match op {
+                "==" => s == q,
+                "=" => s == q,
+                "!=" => s != q,
+                ">" => s > q,
+                ">=" => s >= q,
+                "<" => s < q,
+                "<=" => s <= q,
+                "%" => wild::is_match_u8(s.as_bytes(), q.as_bytes()),
+                "!%" => !wild::is_match_u8(s.as_bytes(), q.as_bytes()),
                 _ => false,
             },
+            _ => false,
+        },
 
-            QueryValue::F64(q) => match v {
-                Value::Number(n) => match *op {
-                    "=" => (n.to_f64() - q).abs() < f64::EPSILON,
-                    "==" => (n.to_f64() - q).abs() < f64::EPSILON,
-                    "!=" => (n.to_f64() - q).abs() > f64::EPSILON,
-                    "<" => n.to_f64() < q,
-                    "<=" => n.to_f64() <= q,
-                    ">" => n.to_f64() > q,
-                    ">=" => n.to_f64() >= q,
-                    _ => false,
-                },
+        QueryValue::F64(q) => match *v {
+            Value::Number(ref n) => match op {
+                "=" => (n.to_f64() - q).abs() < f64::EPSILON,
+                "==" => (n.to_f64() - q).abs() < f64::EPSILON,
+                "!=" => (n.to_f64() - q).abs() > f64::EPSILON,
+                "<" => n.to_f64() < q,
+                "<=" => n.to_f64() <= q,
+                ">" => n.to_f64() > q,
+                ">=" => n.to_f64() >= q,
                 _ => false,
             },
+            _ => false,
+        },
 
-            QueryValue::Boolean(q) => match v {
-                Value::Boolean(b) => match *op {
-                    "=" => b == q,
-                    "==" => b == q,
-                    "!=" => b != q,
-                    _ => false,
-                },
+        QueryValue::Boolean(q) => match *v {
+            Value::Boolean(b) => match op {
+                "=" => b == q,
+                "==" => b == q,
+                "!=" => b != q,
                 _ => false,
             },
+            _ => false,
+        },
 
-            QueryValue::Null => match *op {
-                "=" => v == Value::Null,
-                "==" => v == Value::Null,
-                "!=" => v != Value::Null,
+        #[cfg(feature = "regex")]
+        QueryValue::Regex(ref re) => match *v {
+            Value::String(ref s) => match op {
+                "=~" => re.is_match(s),
+                "!~" => !re.is_match(s),
                 _ => false,
             },
             _ => false,
-        }
+        },
+
+        QueryValue::Null => match op {
+            "=" => *v == Value::Null,
+            "==" => *v == Value::Null,
+            "!=" => *v != Value::Null,
+            _ => false,
+        },
+
+        QueryValue::Array(ref items) => match op {
+            "in" => items.iter().any(|q| compare(v, "==", q, fold)),
+            "!in" => !items.iter().any(|q| compare(v, "==", q, fold)),
+            _ => false,
+        },
+        _ => false,
     }
 }
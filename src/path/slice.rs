@@ -0,0 +1,165 @@
+use std::str;
+
+use crate::{util, Error, Result};
+
+/// An array slice, such as `[2:5]`, `[:10]`, `[-3:]` or `[::2]`.
+/// The bounds are resolved like Python does.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Slice {
+    pub start: Option<isize>,
+    pub end:   Option<isize>,
+    pub step:  isize,
+}
+
+impl Slice {
+    /// Returns true if the length of the array is needed to resolve
+    /// the bounds, otherwise the elements can be taken while reading.
+    pub fn needs_len(&self) -> bool {
+        self.step < 0 || self.start.is_some_and(|i| i < 0) || self.end.is_some_and(|i| i < 0)
+    }
+
+    /// Returns the first index and the number of elements to take when
+    /// the length is not needed.
+    pub fn bounds(&self) -> (usize, Option<usize>) {
+        let start = self.start.unwrap_or(0).max(0) as usize;
+        let take = self
+            .end
+            .map(|end| (end.max(0) as usize).saturating_sub(start));
+        (start, take)
+    }
+
+    /// Returns the indices in the order of the slice.
+    pub fn indices(&self, len: usize) -> Vec<usize> {
+        let len = len as isize;
+        let resolve = |i: isize, min: isize, max: isize| {
+            let i = if i < 0 { i + len } else { i };
+            i.max(min).min(max)
+        };
+
+        let mut indices = Vec::new();
+        if self.step > 0 {
+            let start = self.start.map_or(0, |i| resolve(i, 0, len));
+            let end = self.end.map_or(len, |i| resolve(i, 0, len));
+            let mut i = start;
+            while i < end {
+                indices.push(i as usize);
+                i = match i.checked_add(self.step) {
+                    Some(i) => i,
+                    None => break,
+                };
+            }
+        } else {
+            let start = self.start.map_or(len - 1, |i| resolve(i, -1, len - 1));
+            let end = self.end.map_or(-1, |i| resolve(i, -1, len - 1));
+            let mut i = start;
+            while i > end {
+                indices.push(i as usize);
+                i = match i.checked_add(self.step) {
+                    Some(i) => i,
+                    None => break,
+                };
+            }
+        }
+
+        indices
+    }
+}
+
+/// Parses a slice, the first byte must be `[`. Returns the slice and the
+/// offset of the closing `]`, or `None` if it's not a slice.
+///
+/// A bracket with a `:` and without the separators of the selectors is a
+/// slice, `Error::Path` is returned if a bound or the step is invalid.
+pub fn parse_slice(v: &[u8]) -> Result<Option<(Slice, usize)>> {
+    if v.first() != Some(&b'[') {
+        return Ok(None);
+    }
+
+    let end = match v.iter().position(|&b| b == b']') {
+        Some(end) => end,
+        None => return Ok(None),
+    };
+    let body = &v[1..end];
+    let selector = |b: &u8| b",.\"[({".contains(b);
+    if !body.contains(&b':') || body.iter().any(selector) {
+        return Ok(None);
+    }
+
+    fn bound(part: Option<&[u8]>) -> Result<Option<isize>> {
+        match part.map(util::trim_space_u8) {
+            None | Some(b"") => Ok(None),
+            Some(p) => str::from_utf8(p)
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .map(Some)
+                .ok_or(Error::Path),
+        }
+    }
+
+    let mut parts = body.split(|&b| b == b':');
+    let start = bound(parts.next())?;
+    let end_bound = bound(parts.next())?;
+    let step = bound(parts.next())?.unwrap_or(1);
+    if parts.next().is_some() || step == 0 {
+        return Err(Error::Path);
+    }
+
+    Ok(Some((
+        Slice {
+            start,
+            end: end_bound,
+            step,
+        },
+        end,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slice(v: &str) -> Slice {
+        parse_slice(v.as_bytes()).unwrap().unwrap().0
+    }
+
+    #[test]
+    fn test_parse_slice() {
+        assert_eq!(
+            parse_slice(b"[2:5]"),
+            Ok(Some((
+                Slice {
+                    start: Some(2),
+                    end:   Some(5),
+                    step:  1,
+                },
+                4
+            )))
+        );
+        assert_eq!(slice("[:10]").end, Some(10));
+        assert_eq!(slice("[::2]").step, 2);
+        assert_eq!(slice("[-3:]").start, Some(-3));
+        assert_eq!(parse_slice(b"[a,b]"), Ok(None));
+        assert_eq!(parse_slice(b"[a:b.c]"), Ok(None));
+        assert_eq!(parse_slice(b"[1]"), Ok(None));
+        assert_eq!(parse_slice(b"{1:2}"), Ok(None));
+        assert_eq!(parse_slice(b"[::0]"), Err(Error::Path));
+        assert_eq!(parse_slice(b"[1:2:0]"), Err(Error::Path));
+        assert_eq!(parse_slice(b"[a:b]"), Err(Error::Path));
+        assert_eq!(parse_slice(b"[1:2:3:4]"), Err(Error::Path));
+        assert_eq!(parse_slice(b"[1:99999999999999999999]"), Err(Error::Path));
+    }
+
+    #[test]
+    fn test_slice_indices() {
+        assert_eq!(slice("[2:5]").indices(10), vec![2, 3, 4]);
+        assert_eq!(slice("[:3]").indices(2), vec![0, 1]);
+        assert_eq!(slice("[::3]").indices(7), vec![0, 3, 6]);
+        assert_eq!(slice("[-2:]").indices(5), vec![3, 4]);
+        assert_eq!(slice("[:-3]").indices(5), vec![0, 1]);
+        assert_eq!(slice("[::-1]").indices(3), vec![2, 1, 0]);
+        assert_eq!(slice("[3:0:-2]").indices(5), vec![3, 1]);
+        assert!(slice("[5:2]").indices(10).is_empty());
+        assert_eq!(slice("[-1::9223372036854775807]").indices(3), vec![2]);
+        assert_eq!(slice("[1::-9223372036854775808]").indices(3), vec![1]);
+    }
+}
@@ -51,7 +51,7 @@ fn last_of_name(v: &[u8]) -> &[u8] {
     v
 }
 
-pub fn parse_selectors(v: &[u8]) -> (Vec<SubSelector>, usize, bool) {
+pub fn parse_selectors(v: &[u8]) -> (Vec<SubSelector<'_>>, usize, bool) {
     let mut i = 0;
     let mut depth = 0;
     let mut start = 0;
@@ -87,17 +87,13 @@ pub fn parse_selectors(v: &[u8]) -> (Vec<SubSelector>, usize, bool) {
 
                 continue;
             }
-            b':' => {
-                if depth == 1 {
-                    colon = i;
-                }
+            b':' if depth == 1 => {
+                colon = i;
             }
-            b',' => {
-                if depth == 1 {
-                    push_sel!();
-                    colon = 0;
-                    start = i;
-                }
+            b',' if depth == 1 => {
+                push_sel!();
+                colon = 0;
+                start = i + 1;
             }
             b'[' | b'(' | b'{' => {
                 depth += 1;
@@ -136,4 +132,14 @@ mod tests {
         let path = r#"[name,a]"#;
         let (sels, length, ok) = parse_selectors(path.as_bytes());
     }
+
+    #[test]
+    fn test_parse_selectors_after_comma() {
+        let (sels, _, ok) = parse_selectors(br#"{name.first,age,"m":friends.0}"#);
+        assert!(ok);
+        let names: Vec<&[u8]> = sels.iter().map(|sel| sel.name).collect();
+        assert_eq!(names, vec![&b"first"[..], b"age", b"m"]);
+        let paths: Vec<&[u8]> = sels.iter().map(|sel| sel.path).collect();
+        assert_eq!(paths, vec![&b"name.first"[..], b"age", b"friends.0"]);
+    }
 }
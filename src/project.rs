@@ -0,0 +1,85 @@
+use std::ops::Range;
+
+use crate::{
+    edit::{self, Child},
+    util, Result,
+};
+
+/// Keeps only the values at the paths and returns the new JSON. Unlike
+/// the selectors, the values stay at their places with their keys, and
+/// the members and elements around them keep their order. The elements
+/// of arrays keep their indexes, the dropped ones before a kept element
+/// are replaced with `null` and the ones after the last kept are removed.
+/// ```
+/// use ajson::Result;
+/// fn main() -> Result<()> {
+///     let json = r#"{"id": 1, "user": {"name": "Tom", "age": 37}, "items": [{"sku": "a", "n": 1}, {"sku": "b", "n": 2}]}"#;
+///
+///     assert_eq!(
+///         ajson::project(json, &["id", "user.name", "items.#.sku"])?,
+///         r#"{"id": 1, "user": {"name": "Tom"}, "items": [{"sku": "a"}, {"sku": "b"}]}"#
+///     );
+///     Ok(())
+/// }
+/// ```
+/// The paths may use the same syntax as [`delete`](fn.delete.html).
+pub fn project(json: &str, paths: &[&str]) -> Result<String> {
+    let mut kept = Vec::new();
+    for path in paths {
+        edit::walk(json, path, &mut |children: &[Child], matched: &[usize]| {
+            kept.extend(matched.iter().map(|&i| children[i].value_span()));
+        })?;
+    }
+    kept.sort_unstable_by_key(|span| span.start);
+
+    let bytes = json.as_bytes();
+    let mut replaced = Vec::new();
+    let root = util::trim_whitespace_u8(bytes);
+    if matches!(root.first(), Some(b'{') | Some(b'[')) {
+        prune(bytes, root, &kept, &mut replaced)?;
+    }
+    Ok(edit::replace_ranges(json, replaced))
+}
+
+/// Collects the replacements of the children which aren't kept and have
+/// no kept value inside, the spans of the kept values must be sorted.
+fn prune(
+    bytes: &[u8],
+    container: &[u8],
+    kept: &[Range<usize>],
+    replaced: &mut Vec<(Range<usize>, String)>,
+) -> Result<()> {
+    let children = edit::children(bytes, container)?;
+    let mut removed = Vec::new();
+    for (i, child) in children.iter().enumerate() {
+        let span = child.value_span();
+        let from = kept.partition_point(|k| k.start < span.start);
+        let to = kept.partition_point(|k| k.start < span.end);
+
+        match &kept[from..to] {
+            [] => removed.push(i),
+            // the value itself is kept
+            [first, ..] if first.start == span.start => (),
+            _ => prune(bytes, &bytes[span], kept, replaced)?,
+        }
+    }
+
+    // the elements before the last kept one stay as `null`s to keep the
+    // indexes
+    if container[0] == b'[' {
+        let last_kept = (0..children.len())
+            .rev()
+            .find(|i| removed.binary_search(i).is_err());
+        if let Some(last_kept) = last_kept {
+            let at = removed.partition_point(|&i| i < last_kept);
+            for i in removed.drain(..at) {
+                replaced.push((children[i].value_span(), "null".to_string()));
+            }
+        }
+    }
+
+    let mut ranges = Vec::new();
+    edit::push_removals(&children, &removed, &mut ranges);
+    replaced.extend(ranges.into_iter().map(|range| (range, String::new())));
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::{
+    edit::{self, Child},
+    element::Element,
+    unescape::escape,
+    Result,
+};
+
+/// How [`redact`](fn.redact.html) replaces the values.
+#[derive(Debug, Clone, Copy)]
+pub enum Mask<'a> {
+    /// Replaces the values with the string, such as `"***"`.
+    Text(&'a str),
+    /// Replaces the values with the 16 hex digits of the FNV-1a hash of
+    /// their JSON, as a string. The equal values have the same hash, but it
+    /// isn't a cryptographic one and the short values can be guessed.
+    Hash,
+    /// Replaces the values with the empty ones of the same type, `""`, `0`,
+    /// `false`, `{}` and `[]`, the `null`s are kept.
+    Placeholder,
+}
+
+impl<'a> Mask<'a> {
+    fn mask(&self, value: &Element) -> String {
+        match self {
+            Mask::Text(text) => escape(text),
+            Mask::Hash => {
+                let raw = value.raw().unwrap_or_default();
+                format!(r#""{:016x}""#, fnv1a(raw))
+            }
+            Mask::Placeholder => match value {
+                Element::String(..) => r#""""#,
+                Element::Number(_) => "0",
+                Element::Boolean(_) => "false",
+                Element::Object(_) => "{}",
+                Element::Array(_) => "[]",
+                _ => "null",
+            }
+            .to_string(),
+        }
+    }
+}
+
+/// Replaces the values at the paths with the mask and returns the new JSON,
+/// the rest of the JSON is kept as it is. The wildcards and the `#(...)#`
+/// queries mask all the matched values.
+/// ```
+/// use ajson::{Mask, Result};
+/// fn main() -> Result<()> {
+///     let json = r#"{"user": {"email": "tom@example.com", "age": 37}, "token": "abc"}"#;
+///
+///     assert_eq!(
+///         ajson::redact(json, &["user.email", "token"], Mask::Text("***"))?,
+///         r#"{"user": {"email": "***", "age": 37}, "token": "***"}"#
+///     );
+///     assert_eq!(
+///         ajson::redact(json, &["*.age", "token"], Mask::Placeholder)?,
+///         r#"{"user": {"email": "tom@example.com", "age": 0}, "token": ""}"#
+///     );
+///     Ok(())
+/// }
+/// ```
+/// The paths may use the same syntax as [`delete`](fn.delete.html).
+pub fn redact(json: &str, paths: &[&str], mask: Mask) -> Result<String> {
+    let mut masked = Vec::new();
+    for path in paths {
+        edit::walk(json, path, &mut |children: &[Child], matched: &[usize]| {
+            for &i in matched {
+                let child = &children[i];
+                masked.push((child.value_span(), mask.mask(&child.value)));
+            }
+        })?;
+    }
+
+    Ok(edit::replace_ranges(json, masked))
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
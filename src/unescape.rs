@@ -1,5 +1,3 @@
-use std::u32;
-
 pub fn unescape(v: &[u8]) -> String {
     let mut s = Vec::with_capacity(v.len());
     let mut i = 0;
@@ -87,15 +85,57 @@ pub fn unescape(v: &[u8]) -> String {
     unsafe { String::from_utf8_unchecked(s) }
 }
 
+/// Escapes the string as a JSON string, with quotes.
+/// ```
+/// assert_eq!(ajson::escape("say \"hi\"\n"), r#""say \"hi\"\n""#);
+/// ```
+pub fn escape(s: &str) -> String {
+    let mut buffer = String::with_capacity(s.len() + 2);
+    escape_into(s, &mut buffer);
+    buffer
+}
+
+/// Same as [`escape`], but writes into the buffer.
+pub(crate) fn escape_into(s: &str, buffer: &mut String) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    buffer.push('"');
+    let mut start = 0;
+    for (i, b) in s.bytes().enumerate() {
+        if !matches!(b, b'"' | b'\\' | 0x00..=0x1F) {
+            continue;
+        }
+
+        buffer.push_str(&s[start..i]);
+        start = i + 1;
+        match b {
+            b'"' => buffer.push_str("\\\""),
+            b'\\' => buffer.push_str("\\\\"),
+            b'\n' => buffer.push_str("\\n"),
+            b'\r' => buffer.push_str("\\r"),
+            b'\t' => buffer.push_str("\\t"),
+            0x08 => buffer.push_str("\\b"),
+            0x0C => buffer.push_str("\\f"),
+            _ => {
+                buffer.push_str("\\u00");
+                buffer.push(HEX[(b >> 4) as usize] as char);
+                buffer.push(HEX[(b & 0xF) as usize] as char);
+            }
+        }
+    }
+    buffer.push_str(&s[start..]);
+    buffer.push('"');
+}
+
 fn u8s_to_u32(v: &[u8]) -> u32 {
     u8_to_u32(v[0]) << 12 | u8_to_u32(v[1]) << 8 | u8_to_u32(v[2]) << 4 | u8_to_u32(v[3])
 }
 
 fn u8_to_u32(b: u8) -> u32 {
     let r = match b {
-        b'0'..=b'9' => (b - b'0'),
-        b'a'..=b'f' => (b + 10 - b'a'),
-        b'A'..=b'F' => (b + 10 - b'A'),
+        b'0'..=b'9' => b - b'0',
+        b'a'..=b'f' => b + 10 - b'a',
+        b'A'..=b'F' => b + 10 - b'A',
         _ => panic!("unexpected byte"),
     };
     r as u32
@@ -135,4 +175,15 @@ mod tests {
             unescape(r#"\ud83d\udd13, \ud83c\udfc3 OK: \u2764\ufe0f"#.as_bytes())
         );
     }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("ajson"), r#""ajson""#);
+        assert_eq!(escape(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(escape("\t\n\r\u{8}\u{c}\u{1}"), r#""\t\n\r\b\f\u0001""#);
+        assert_eq!(escape("été 🦀"), "\"été 🦀\"");
+
+        let s = "\"\\\u{0}\u{1f}x";
+        assert_eq!(unescape(escape(s).as_bytes()), format!("\"{}\"", s));
+    }
 }
@@ -2,6 +2,17 @@ pub fn trim_space_u8(v: &[u8]) -> &[u8] {
     trim_u8(v, b' ')
 }
 
+/// Trims the JSON whitespace, spaces, tabs and line breaks.
+pub fn trim_whitespace_u8(v: &[u8]) -> &[u8] {
+    let is_space = |b: &u8| matches!(b, b' ' | b'\t' | b'\n' | b'\r');
+    let start = v.iter().position(|b| !is_space(b)).unwrap_or(v.len());
+    let end = v
+        .iter()
+        .rposition(|b| !is_space(b))
+        .map_or(start, |i| i + 1);
+    &v[start..end]
+}
+
 pub fn trim_u8(v: &[u8], b: u8) -> &[u8] {
     let length = v.len();
     let mut i = 0;
@@ -60,3 +71,64 @@ pub fn equal_escape_u8(a: &[u8], b: &[u8]) -> bool {
 
     !(j != b.len() || i != a.len())
 }
+
+/// Folds the case of a string for case-insensitive comparisons, it is
+/// close to the full Unicode case folding, e.g. `ß` and `SS` both fold to
+/// `ss`.
+pub fn fold(s: &str) -> String {
+    if s.is_ascii() {
+        return s.to_ascii_lowercase();
+    }
+
+    s.chars()
+        .flat_map(char::to_uppercase)
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Compares two strings case-insensitively.
+pub fn fold_eq(a: &str, b: &str) -> bool {
+    if a.is_ascii() && b.is_ascii() {
+        return a.eq_ignore_ascii_case(b);
+    }
+
+    fold(a) == fold(b)
+}
+
+/// Removes the escape characters from a path.
+pub fn strip_escapes(v: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(v.len());
+    let mut i = 0;
+    while i < v.len() {
+        if v[i] == b'\\' && i + 1 < v.len() {
+            i += 1;
+        }
+        out.push(v[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Joins the path and the part with a `.`, the part is returned if the path
+/// is empty.
+pub fn join_path(path: &str, part: &str) -> String {
+    match path {
+        "" => part.to_string(),
+        _ => format!("{}.{}", path, part),
+    }
+}
+
+/// Escapes the characters of the key which have a meaning in paths.
+pub fn escape_key(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+    for c in key.chars() {
+        if matches!(
+            c,
+            '.' | '|' | '\\' | '*' | '?' | '#' | '@' | '!' | '~' | '[' | '(' | '{'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -1,6 +1,6 @@
 use std::{borrow::Cow, collections::HashMap, fmt, fmt::Formatter, str};
 
-use crate::{number::Number, parser, path::Path, Result};
+use crate::{escape, number::Number, parser, path::Path, Options, Result};
 
 /// Represents JSON valuue.
 #[derive(PartialEq, Eq, Clone)]
@@ -47,10 +47,11 @@ impl<'a> Value<'a> {
     ///     Ok(())
     /// }
     /// ```
-    pub fn get(&self, path: &'a str) -> Result<Option<Value>> {
+    pub fn get(&self, path: &'a str) -> Result<Option<Value<'_>>> {
         match self {
             Value::Array(s) | Value::Object(s) => {
-                let p = Path::from_slice(path.as_ref())?;
+                let opts = Options::new().root(s.as_bytes());
+                let p = Path::from_slice_with(path.as_ref(), opts)?;
                 let (a, _left) = parser::bytes_get(s.as_bytes(), &p)?;
                 Ok(a.map(|el| el.to_value()))
             }
@@ -60,6 +61,24 @@ impl<'a> Value<'a> {
 }
 
 impl<'a> Value<'a> {
+    /// Converts the `Value` into one which owns its data.
+    /// ```
+    /// let v = ajson::get(r#"{"name":"ajson"}"#, "name").unwrap().unwrap();
+    /// let v: ajson::Value<'static> = v.into_owned();
+    /// assert_eq!(v, "ajson");
+    /// ```
+    pub fn into_owned(self) -> Value<'static> {
+        match self {
+            Value::String(s) => Value::String(Cow::Owned(s.into_owned())),
+            Value::Number(n) => Value::Number(n.into_owned()),
+            Value::Usize(n) => Value::Usize(n),
+            Value::Object(s) => Value::Object(Cow::Owned(s.into_owned())),
+            Value::Array(s) => Value::Array(Cow::Owned(s.into_owned())),
+            Value::Boolean(b) => Value::Boolean(b),
+            Value::Null => Value::Null,
+        }
+    }
+
     /// Returns true if the `Value` is a JSON string.
     /// ```
     /// let v = ajson::get(r#"{"name":"ajson"}"#, "name").unwrap().unwrap();
@@ -106,6 +125,24 @@ impl<'a> std::fmt::Display for Value<'a> {
 }
 
 impl<'a> Value<'a> {
+    /// Returns the value as JSON, the strings are quoted and escaped.
+    /// ```
+    /// use ajson::Value;
+    /// assert_eq!(Value::from("say \"hi\"").to_json(), r#""say \"hi\"""#);
+    /// assert_eq!(Value::from(1.5).to_json(), "1.5");
+    /// ```
+    pub fn to_json(&self) -> Cow<'_, str> {
+        match self {
+            Value::String(s) => Cow::Owned(escape(s)),
+            Value::Object(s) | Value::Array(s) => Cow::Borrowed(s),
+            Value::Number(n) => Cow::Borrowed(n.as_str()),
+            Value::Usize(n) => Cow::Owned(n.to_string()),
+            Value::Boolean(true) => Cow::Borrowed("true"),
+            Value::Boolean(false) => Cow::Borrowed("false"),
+            Value::Null => Cow::Borrowed("null"),
+        }
+    }
+
     pub fn as_str(&self) -> Option<&str> {
         match self {
             Value::String(s) => Some(s),
@@ -144,14 +181,14 @@ impl<'a> Value<'a> {
         }
     }
 
-    pub fn as_vec(&self) -> Option<Vec<Value>> {
+    pub fn as_vec(&self) -> Option<Vec<Value<'_>>> {
         match self {
             Value::Array(s) => parser::bytes_to_vec(s.as_bytes()).ok(),
             _ => None,
         }
     }
 
-    pub fn as_object(&self) -> Option<HashMap<&str, Value>> {
+    pub fn as_object(&self) -> Option<HashMap<&str, Value<'_>>> {
         match self {
             Value::Object(s) => parser::bytes_to_map(s.as_bytes()).ok(),
             _ => None,
@@ -160,23 +197,23 @@ impl<'a> Value<'a> {
 }
 
 fn eq_f64(value: &Value, other: f64) -> bool {
-    value.as_f64().map_or(false, |i| i == other)
+    value.as_f64() == Some(other)
 }
 
 fn eq_i64(value: &Value, other: i64) -> bool {
-    value.as_i64().map_or(false, |i| i == other)
+    value.as_i64() == Some(other)
 }
 
 fn eq_u64(value: &Value, other: u64) -> bool {
-    value.as_u64().map_or(false, |i| i == other)
+    value.as_u64() == Some(other)
 }
 
 fn eq_bool(value: &Value, other: bool) -> bool {
-    value.as_bool().map_or(false, |i| i == other)
+    value.as_bool() == Some(other)
 }
 
 fn eq_str(value: &Value, other: &str) -> bool {
-    value.as_str().map_or(false, |i| i == other)
+    value.as_str() == Some(other)
 }
 
 impl<'a> PartialEq<str> for Value<'a> {
@@ -187,7 +224,7 @@ impl<'a> PartialEq<str> for Value<'a> {
 
 impl<'a> PartialEq<&'a str> for Value<'a> {
     fn eq(&self, other: &&str) -> bool {
-        eq_str(self, *other)
+        eq_str(self, other)
     }
 }
 
@@ -199,7 +236,7 @@ impl<'a> PartialEq<Value<'a>> for str {
 
 impl<'a> PartialEq<Value<'a>> for &'a str {
     fn eq(&self, other: &Value) -> bool {
-        eq_str(other, *self)
+        eq_str(other, self)
     }
 }
 
@@ -251,3 +288,55 @@ partialeq_numeric! {
     eq_f64[f32 f64]
     eq_bool[bool]
 }
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(s: &'a str) -> Value<'a> {
+        Value::String(Cow::Borrowed(s))
+    }
+}
+
+impl<'a> From<String> for Value<'a> {
+    fn from(s: String) -> Value<'a> {
+        Value::String(Cow::Owned(s))
+    }
+}
+
+impl<'a> From<bool> for Value<'a> {
+    fn from(b: bool) -> Value<'a> {
+        Value::Boolean(b)
+    }
+}
+
+macro_rules! from_numeric {
+    ($($variant:ident [$($ty:ty)*])*) => {
+        $($(
+            impl<'a> From<$ty> for Value<'a> {
+                fn from(n: $ty) -> Value<'a> {
+                    Value::Number(Number::$variant(Cow::Owned(n.to_string())))
+                }
+            }
+        )*)*
+    }
+}
+
+from_numeric! {
+    I64[i8 i16 i32 i64 isize]
+    U64[u8 u16 u32 u64 usize]
+}
+
+/// The numbers which JSON can't represent, `NaN` and the infinities, are
+/// converted into `null`.
+impl<'a> From<f64> for Value<'a> {
+    fn from(n: f64) -> Value<'a> {
+        match n.is_finite() {
+            true => Value::Number(Number::F64(Cow::Owned(n.to_string()))),
+            false => Value::Null,
+        }
+    }
+}
+
+impl<'a> From<f32> for Value<'a> {
+    fn from(n: f32) -> Value<'a> {
+        Value::from(n as f64)
+    }
+}
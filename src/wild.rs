@@ -1,15 +1,95 @@
-use std::str;
+/// Matches the string against a glob pattern, `*` matches any number of
+/// characters, `?` matches exactly one character and `\` escapes the
+/// next character. The pattern has to match the whole string.
+pub fn is_match_u8(s: &[u8], pattern: &[u8]) -> bool {
+    let (mut i, mut j) = (0, 0);
+    // the pattern after the last `*`, and where its match starts in the string
+    let mut star: Option<(usize, usize)> = None;
 
-use regex::Regex;
+    while i < s.len() {
+        match pattern.get(j) {
+            Some(b'*') => {
+                j += 1;
+                star = Some((j, i));
+                continue;
+            }
+            Some(b'?') => {
+                i = (i + char_len(s[i])).min(s.len());
+                j += 1;
+                continue;
+            }
+            Some(&b) => {
+                let (b, n) = match (b, pattern.get(j + 1)) {
+                    (b'\\', Some(&escaped)) => (escaped, 2),
+                    _ => (b, 1),
+                };
+                if s[i] == b {
+                    i += 1;
+                    j += n;
+                    continue;
+                }
+            }
+            None => (),
+        }
 
-pub fn is_match(k1: &str, pattern: &str) -> bool {
-    let pattern = &pattern.replace("?", ".").replace("*", ".+?");
-    Regex::new(pattern).unwrap().is_match(k1)
+        // backtrack, let the last `*` match one more character
+        match star {
+            Some((star_j, star_i)) => {
+                let star_i = (star_i + char_len(s[star_i])).min(s.len());
+                star = Some((star_j, star_i));
+                i = star_i;
+                j = star_j;
+            }
+            None => return false,
+        }
+    }
+
+    pattern[j..].iter().all(|&b| b == b'*')
+}
+
+/// Returns the length of the UTF-8 character by its first byte.
+fn char_len(b: u8) -> usize {
+    match b {
+        0xf0..=0xff => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _ => 1,
+    }
 }
 
-pub fn is_match_u8(k1: &[u8], pattern: &[u8]) -> bool {
-    let key = str::from_utf8(k1).unwrap();
-    let pat = str::from_utf8(pattern).unwrap();
-    let pat = &pat.replace("?", ".").replace("*", ".+?");
-    Regex::new(pat).unwrap().is_match(key)
+#[cfg(test)]
+mod tests {
+    use super::is_match_u8;
+
+    fn is_match(s: &str, pattern: &str) -> bool {
+        is_match_u8(s.as_bytes(), pattern.as_bytes())
+    }
+
+    #[test]
+    fn test_is_match() {
+        assert!(is_match("children", "child*"));
+        assert!(is_match("child", "child*"));
+        assert!(is_match("children", "c?ildren"));
+        assert!(is_match("children", "*"));
+        assert!(is_match("", "*"));
+        assert!(is_match("abc", "a*b*c"));
+        assert!(is_match("aXbYbc", "a*bc"));
+        assert!(is_match("été", "?t?"));
+        assert!(is_match("a*b", r"a\*b"));
+        assert!(is_match(r"a\b", r"a\\b"));
+
+        assert!(!is_match("xa", "a*"));
+        assert!(!is_match("ab", "a"));
+        assert!(!is_match("a", "a?"));
+        assert!(!is_match("", "?"));
+        assert!(!is_match("axb", r"a\*b"));
+        assert!(!is_match("abcd", "a*c"));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        assert!(is_match_u8(b"\xff\xfe", b"*"));
+        assert!(is_match_u8(b"a\xff", b"a?"));
+        assert!(!is_match_u8(b"\xff", b"a*"));
+    }
 }
@@ -2,7 +2,7 @@ extern crate ajson;
 extern crate json;
 extern crate serde_json;
 
-use ajson::{get, parse, Result, Value};
+use ajson::{get, get_with, parse, Options, Result, Value};
 
 // #[test]
 // fn test_json_rs_unicode() {
@@ -76,7 +76,7 @@ use ajson::{get, parse, Result, Value};
 //     }
 // }
 
-static BASIC_JSON: &'static str = r#"
+static BASIC_JSON: &str = r#"
 {"age":100, "name":{"here":"B\\\"R"},
 	"noop":{"what is a wren?":"a bird"},
 	"happy":true,"immortal":false,
@@ -114,7 +114,7 @@ static BASIC_JSON: &'static str = r#"
 }
 "#;
 
-static BASIC_JSON2: &'static str = r#"
+static BASIC_JSON2: &str = r#"
 {
     "name": {"first": "Tom", "last": "Anderson"},
     "age":37,
@@ -147,7 +147,7 @@ fn test_example() -> Result<()> {
         r.get("children")?.unwrap().as_vec().unwrap(),
         vec!["Sara", "Alex", "Jack"]
     );
-    assert_eq!(r.get("children.#")?.unwrap(), 3 as u64);
+    assert_eq!(r.get("children.#")?.unwrap(), 3_u64);
     assert_eq!(r.get("children.1")?.unwrap(), "Alex");
     assert_eq!(r.get("child*.2")?.unwrap(), "Jack");
     assert_eq!(r.get("c?ildren.0")?.unwrap(), "Sara");
@@ -269,7 +269,7 @@ fn test_basic_2() -> Result<()> {
 
 #[test]
 fn test_basic_3() -> Result<()> {
-    let t = ajson::parse(BASIC_JSON)?.unwrap();
+    let _t = ajson::parse(BASIC_JSON)?.unwrap();
     let json = "-102";
     let t = parse(json)?.unwrap();
     assert_eq!(t, -102);
@@ -304,15 +304,15 @@ fn test_basic_3() -> Result<()> {
 
 #[test]
 fn test_basic_4() -> Result<()> {
-    assert_eq!(get(&BASIC_JSON, "items.3.tags.#")?.unwrap(), 3 as f64);
-    assert_eq!(get(&BASIC_JSON, "items.3.points.1.#")?.unwrap(), 2 as f64);
-    assert_eq!(get(&BASIC_JSON, "items.#")?.unwrap(), 8 as f64);
-    assert_eq!(get(&BASIC_JSON, "vals.#")?.unwrap(), 4 as f64);
-    assert!(!get(&BASIC_JSON, "name.last")?.is_some());
-    assert_eq!(get(&BASIC_JSON, "name.here")?.unwrap(), "B\\\"R");
-
-    assert_eq!(get(&BASIC_JSON, "arr.#")?.unwrap(), 6 as f64);
-    assert_eq!(get(&BASIC_JSON, "arr.3.hello")?.unwrap(), "world");
+    assert_eq!(get(BASIC_JSON, "items.3.tags.#")?.unwrap(), 3_f64);
+    assert_eq!(get(BASIC_JSON, "items.3.points.1.#")?.unwrap(), 2_f64);
+    assert_eq!(get(BASIC_JSON, "items.#")?.unwrap(), 8_f64);
+    assert_eq!(get(BASIC_JSON, "vals.#")?.unwrap(), 4_f64);
+    assert!(get(BASIC_JSON, "name.last")?.is_none());
+    assert_eq!(get(BASIC_JSON, "name.here")?.unwrap(), "B\\\"R");
+
+    assert_eq!(get(BASIC_JSON, "arr.#")?.unwrap(), 6_f64);
+    assert_eq!(get(BASIC_JSON, "arr.3.hello")?.unwrap(), "world");
     // Need to Fix
     // assert_eq!(get(&BASIC_JSON, "name.first"), "tom");
     // assert_eq!(get(&BASIC_JSON, "name.last").unwrap(), "");
@@ -323,16 +323,16 @@ fn test_basic_4() -> Result<()> {
 
 #[test]
 fn test_basic_5() -> Result<()> {
-    assert_eq!(get(&BASIC_JSON, "age")?.unwrap(), 100);
-    assert_eq!(get(&BASIC_JSON, "happy")?.unwrap(), true);
-    assert_eq!(get(&BASIC_JSON, "immortal")?.unwrap(), false);
+    assert_eq!(get(BASIC_JSON, "age")?.unwrap(), 100);
+    assert_eq!(get(BASIC_JSON, "happy")?.unwrap(), true);
+    assert_eq!(get(BASIC_JSON, "immortal")?.unwrap(), false);
 
-    let t = get(&BASIC_JSON, "noop")?.unwrap();
+    let t = get(BASIC_JSON, "noop")?.unwrap();
     let m = t.as_object().unwrap();
     assert_eq!(m.len(), 1);
     assert_eq!(m["what is a wren?"], "a bird");
 
-    let r = parse(&BASIC_JSON)?.unwrap();
+    let r = parse(BASIC_JSON)?.unwrap();
     assert_eq!(
         r.as_object().unwrap()["loggy"].as_object().unwrap()["programmers"]
             .as_vec()
@@ -370,16 +370,16 @@ fn test_is_array_is_object() -> Result<()> {
 #[test]
 fn test_plus_53_bit_ints() -> Result<()> {
     let json = r#"{"IdentityData":{"GameInstanceId":634866135153775564}}"#;
-    let v = get(&json, "IdentityData.GameInstanceId")?.unwrap();
-    assert_eq!(v, 634866135153775564 as u64);
-    assert_eq!(v, 634866135153775564 as i64);
-    assert_eq!(v, 634866135153775616.0 as f64);
+    let v = get(json, "IdentityData.GameInstanceId")?.unwrap();
+    assert_eq!(v, 634866135153775564_u64);
+    assert_eq!(v, 634866135153775564_i64);
+    assert_eq!(v, 634866135153775616.0_f64);
 
     let json = r#"{"IdentityData":{"GameInstanceId":634866135153775564.88172}}"#;
-    let v = get(&json, "IdentityData.GameInstanceId")?.unwrap();
-    assert_eq!(v, 634866135153775564 as u64);
-    assert_eq!(v, 634866135153775564 as i64);
-    assert_eq!(v, 634866135153775616.88172 as f64);
+    let v = get(json, "IdentityData.GameInstanceId")?.unwrap();
+    assert_eq!(v, 634866135153775564_u64);
+    assert_eq!(v, 634866135153775564_i64);
+    assert_eq!(v, 634866135153775616.88172_f64);
 
     let json = r#"
     {
@@ -399,34 +399,19 @@ fn test_plus_53_bit_ints() -> Result<()> {
     "#;
 
     assert_eq!(get(json, "min_uint53")?.unwrap(), 0);
-    assert_eq!(get(&json, "max_uint53")?.unwrap(), 4503599627370495 as u64);
-    assert_eq!(
-        get(&json, "overflow_uint53")?.unwrap(),
-        4503599627370496 as i64
-    );
-    assert_eq!(get(&json, "min_int53")?.unwrap(), -2251799813685248 as i64);
-    assert_eq!(get(&json, "max_int53")?.unwrap(), 2251799813685247 as i64);
-    assert_eq!(
-        get(&json, "overflow_int53")?.unwrap(),
-        2251799813685248 as i64
-    );
-    assert_eq!(get(&json, "min_uint64")?.unwrap(), 0);
-    assert_eq!(
-        get(&json, "max_uint64")?.unwrap(),
-        18446744073709551615 as u64
-    );
+    assert_eq!(get(json, "max_uint53")?.unwrap(), 4503599627370495_u64);
+    assert_eq!(get(json, "overflow_uint53")?.unwrap(), 4503599627370496_i64);
+    assert_eq!(get(json, "min_int53")?.unwrap(), -2251799813685248_i64);
+    assert_eq!(get(json, "max_int53")?.unwrap(), 2251799813685247_i64);
+    assert_eq!(get(json, "overflow_int53")?.unwrap(), 2251799813685248_i64);
+    assert_eq!(get(json, "min_uint64")?.unwrap(), 0);
+    assert_eq!(get(json, "max_uint64")?.unwrap(), 18446744073709551615_u64);
 
-    assert_eq!(get(&json, "overflow_uint64")?.unwrap(), 0);
-    assert_eq!(
-        get(&json, "min_int64")?.unwrap(),
-        -9223372036854775808 as i64
-    );
-    assert_eq!(
-        get(&json, "max_int64")?.unwrap(),
-        9223372036854775807 as i64
-    );
+    assert_eq!(get(json, "overflow_uint64")?.unwrap(), 0);
+    assert_eq!(get(json, "min_int64")?.unwrap(), -9223372036854775808_i64);
+    assert_eq!(get(json, "max_int64")?.unwrap(), 9223372036854775807_i64);
 
-    assert_eq!(get(&json, "overflow_int64")?.unwrap(), 0);
+    assert_eq!(get(json, "overflow_int64")?.unwrap(), 0);
 
     Ok(())
 }
@@ -458,10 +443,10 @@ fn test_emoji() -> Result<()> {
 
 #[test]
 fn test_parse_any() -> Result<()> {
-    assert_eq!(parse("100")?.unwrap(), 100 as f64);
+    assert_eq!(parse("100")?.unwrap(), 100_f64);
     assert_eq!(parse("true")?.unwrap(), true);
     assert_eq!(parse("false")?.unwrap(), false);
-    assert_eq!(parse("yikes")?.is_some(), false);
+    assert!(parse("yikes")?.is_none());
     Ok(())
 }
 
@@ -672,15 +657,15 @@ fn test_token_raw_for_literal() -> Result<()> {
 #[test]
 fn test_single_array_value() -> Result<()> {
     let json = r#"{"key": "value","key2":[1,2,3,4,"A"]}"#;
-    let array: Vec<Value> = get(&json, "key")?.into_iter().collect();
+    let array: Vec<Value> = get(json, "key")?.into_iter().collect();
 
     assert_eq!(array.len(), 1);
     assert_eq!(array[0], "value");
 
-    let array: Vec<Value> = get(&json, "key2.#")?.into_iter().collect();
+    let array: Vec<Value> = get(json, "key2.#")?.into_iter().collect();
     assert_eq!(array.len(), 1);
 
-    let r = get(&json, "key3")?;
+    let r = get(json, "key3")?;
     assert!(r.is_none());
 
     Ok(())
@@ -732,3 +717,1445 @@ fn test_key_unicode() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_modifiers() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+    assert_eq!(
+        r.get("children.@reverse")?.unwrap().as_vec().unwrap(),
+        vec!["Jack", "Alex", "Sara"]
+    );
+    assert_eq!(r.get("children.@reverse.0")?.unwrap(), "Jack");
+    assert_eq!(
+        r.get("name.@keys")?.unwrap().as_vec().unwrap(),
+        vec!["first", "last"]
+    );
+    assert_eq!(
+        r.get("name.@values")?.unwrap().as_vec().unwrap(),
+        vec!["Tom", "Anderson"]
+    );
+    assert_eq!(r.get("@this.age")?.unwrap(), 37);
+    assert_eq!(r.get("friends.@join.first")?.unwrap(), "Jane");
+    assert_eq!(
+        r.get("friends.@reverse.#.first")?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Jane", "Roger", "Dale"]
+    );
+
+    let v = get("[1,[2],[3,[4,[5]]]]", "@flatten")?.unwrap();
+    assert_eq!(v.as_vec().unwrap().len(), 4);
+    let v = get("[1,[2],[3,[4,[5]]]]", r#"@flatten:{"deep":true}"#)?.unwrap();
+    assert_eq!(v.as_vec().unwrap(), vec![1, 2, 3, 4, 5]);
+
+    // unknown modifiers are treated as plain keys
+    assert_eq!(get(r#"{"@timestamp":1}"#, "@timestamp")?.unwrap(), 1);
+    assert!(get(r#"{"@timestamp":1}"#, "@unknown")?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_modifiers() -> Result<()> {
+    use ajson::{get_with, Modifiers, Options};
+
+    fn mask(v: &Value, args: Option<&str>) -> Value<'static> {
+        let keep = args.and_then(|n| n.parse().ok()).unwrap_or(0);
+        let s = v.to_string();
+        let masked: String = s
+            .chars()
+            .enumerate()
+            .map(|(i, c)| if i < keep { c } else { '*' })
+            .collect();
+        Value::String(masked.into())
+    }
+
+    fn first_friend(v: &Value, _: Option<&str>) -> Value<'static> {
+        v.get("0").unwrap().unwrap().into_owned()
+    }
+
+    Modifiers::register("test_mask", mask);
+    assert_eq!(get(BASIC_JSON2, "name.first.@test_mask")?.unwrap(), "***");
+    assert_eq!(
+        get(BASIC_JSON2, "name.last.@test_mask:2")?.unwrap(),
+        "An******"
+    );
+    assert_eq!(
+        get(BASIC_JSON2, "friends.#.first.@test_mask:1")?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["D***", "R****", "J***"]
+    );
+
+    let mut modifiers = Modifiers::new();
+    modifiers.insert("first_friend", first_friend);
+    let options = Options::new().modifiers(&modifiers);
+    assert!(get(BASIC_JSON2, "friends.@first_friend")?.is_none());
+    assert_eq!(
+        get_with(BASIC_JSON2, "friends.@first_friend.last", options)?.unwrap(),
+        "Murphy"
+    );
+    assert_eq!(
+        get_with(BASIC_JSON2, "friends.@first_friend.nets.1", options)?.unwrap(),
+        "fb"
+    );
+    assert_eq!(
+        get_with(BASIC_JSON2, "[friends.@first_friend.age,age]", options)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec![44, 37]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_pipe() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+    assert_eq!(r.get("children|0")?.unwrap(), "Sara");
+    assert_eq!(r.get("name|last")?.unwrap(), "Anderson");
+    assert_eq!(r.get(r#"friends.#(last=="Murphy")#|#"#)?.unwrap(), 2);
+    assert!(r.get(r#"friends.#(last=="Murphy")#|first"#)?.is_none());
+    assert_eq!(
+        r.get(r#"friends.#(last=="Murphy")#|1.first"#)?.unwrap(),
+        "Jane"
+    );
+    assert_eq!(r.get("friends.#.first|1")?.unwrap(), "Roger");
+    assert_eq!(r.get("friends.#|#")?.unwrap(), 3);
+    assert_eq!(
+        r.get("friends.#.nets|@flatten")?.unwrap().as_vec().unwrap(),
+        vec!["ig", "fb", "tw", "fb", "tw", "ig", "tw"]
+    );
+    assert_eq!(
+        r.get("friends.#.nets.@flatten")?
+            .unwrap()
+            .as_vec()
+            .unwrap()
+            .len(),
+        3
+    );
+    assert_eq!(r.get("[children,age]|0|1")?.unwrap(), "Alex");
+
+    // queries on constructed lists
+    assert_eq!(
+        r.get(r#"friends.@reverse|#(last=="Murphy").first"#)?
+            .unwrap(),
+        "Jane"
+    );
+    assert_eq!(
+        r.get(r#"friends.@reverse|#(age>45)#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Jane", "Roger"]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_negative_index_and_slice() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+    assert_eq!(r.get("children.-1")?.unwrap(), "Jack");
+    assert_eq!(r.get("children.-3")?.unwrap(), "Sara");
+    assert!(r.get("children.-4")?.is_none());
+    assert_eq!(r.get("friends.-1.first")?.unwrap(), "Jane");
+    assert_eq!(r.get("friends.#.first|-2")?.unwrap(), "Roger");
+
+    assert_eq!(
+        r.get("children.[1:]")?.unwrap().as_vec().unwrap(),
+        vec!["Alex", "Jack"]
+    );
+    assert_eq!(
+        r.get("children.[:10]")?.unwrap().as_vec().unwrap(),
+        vec!["Sara", "Alex", "Jack"]
+    );
+    assert_eq!(
+        r.get("children.[::-1]")?.unwrap().as_vec().unwrap(),
+        vec!["Jack", "Alex", "Sara"]
+    );
+    assert_eq!(
+        r.get("friends.[-2:].first")?.unwrap().as_vec().unwrap(),
+        vec!["Roger", "Jane"]
+    );
+    assert_eq!(
+        r.get("friends.[::2].age")?.unwrap().as_vec().unwrap(),
+        vec![44, 47]
+    );
+    assert_eq!(r.get("friends.[0:2]|#")?.unwrap(), 2);
+    assert_eq!(r.get("friends.[0:2]|-1.first")?.unwrap(), "Roger");
+    assert_eq!(r.get("children.[5:]")?.unwrap().as_vec().unwrap().len(), 0);
+    assert_eq!(
+        r.get("friends.#.first|[1:]")?.unwrap().as_vec().unwrap(),
+        vec!["Roger", "Jane"]
+    );
+    assert_eq!(r.get("children.[1:2:0]"), Err(ajson::Error::Path));
+    assert_eq!(r.get("children.[a:b]"), Err(ajson::Error::Path));
+
+    Ok(())
+}
+
+#[test]
+fn test_recursive_descent() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+    assert_eq!(
+        r.get("**.first")?.unwrap().as_vec().unwrap(),
+        vec!["Tom", "Dale", "Roger", "Jane"]
+    );
+    assert_eq!(
+        r.get("**.age")?.unwrap().as_vec().unwrap(),
+        vec![37, 44, 68, 47]
+    );
+    assert_eq!(
+        r.get("**.nets.0")?.unwrap().as_vec().unwrap(),
+        vec!["ig", "fb", "ig"]
+    );
+    assert_eq!(r.get("**.nets|@flatten|#")?.unwrap(), 7);
+    assert_eq!(r.get("friends.**.nets|#")?.unwrap(), 3);
+    assert_eq!(r.get("**.first|-1")?.unwrap(), "Jane");
+    assert_eq!(
+        r.get(r#"**|#(last=="Murphy")#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Dale", "Jane"]
+    );
+    assert_eq!(r.get("name.**")?.unwrap().as_vec().unwrap().len(), 3);
+    assert!(r.get("**.missing")?.unwrap().as_vec().unwrap().is_empty());
+
+    let json = r#"{"a":{"error":1,"b":[{"error":2},{"c":{"error":3}}]}}"#;
+    assert_eq!(
+        get(json, "**.error")?.unwrap().as_vec().unwrap(),
+        vec![1, 2, 3]
+    );
+    assert!(get(r#"{"a":[1,2]}"#, "a.x")?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_json_lines() -> Result<()> {
+    let json = r#"{"name": "Gilbert", "age": 61}
+{"name": "Alexa", "age": 34}
+{"name": "May", "age": 57}
+{"name": "Deloise", "age": 44}
+"#;
+
+    assert_eq!(get(json, "..#")?.unwrap(), 4);
+    assert_eq!(get(json, "..0.name")?.unwrap(), "Gilbert");
+    assert_eq!(get(json, "..3.age")?.unwrap(), 44);
+    assert_eq!(get(json, "..-1.name")?.unwrap(), "Deloise");
+    assert!(get(json, "..4")?.is_none());
+    assert_eq!(
+        get(json, "..#.name")?.unwrap().as_vec().unwrap(),
+        vec!["Gilbert", "Alexa", "May", "Deloise"]
+    );
+    assert_eq!(get(json, r#"..#(name="May").age"#)?.unwrap(), 57);
+    assert_eq!(
+        get(json, "..#(age>45)#.name")?.unwrap().as_vec().unwrap(),
+        vec!["Gilbert", "May"]
+    );
+    assert_eq!(get(json, "..#(age>45)#|#")?.unwrap(), 2);
+    assert_eq!(get(json, "..")?.unwrap().as_vec().unwrap().len(), 4);
+
+    // without the prefix only the first line is read
+    assert_eq!(get(json, "name")?.unwrap(), "Gilbert");
+
+    // only at the start of the path
+    let json = r#"{"a":{".":{"b":1}},"c":[1,2]}"#;
+    assert_eq!(get(json, "a...b")?.unwrap(), 1);
+    assert!(get(json, "a...#")?.is_none());
+    assert!(get(json, "c|..#")?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_query_logic() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+    assert_eq!(
+        r.get(r#"friends.#(age>45 && last=="Murphy").first"#)?
+            .unwrap(),
+        "Jane"
+    );
+    assert_eq!(
+        r.get(r#"friends.#(age<45 || age>60)#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Dale", "Roger"]
+    );
+    assert_eq!(
+        r.get(r#"friends.#(!(last=="Murphy"))#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Roger"]
+    );
+    assert_eq!(
+        r.get(r#"friends.#(age>40 && (last=="Murphy" || first=="Roger") && age<50)#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Dale", "Jane"]
+    );
+    assert_eq!(
+        r.get(r#"friends.#(nets.#(=="fb") && !(age>60))#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Dale"]
+    );
+    assert_eq!(
+        r.get(r#"friends.#(first=="a && b)" || age==68).first"#)?
+            .unwrap(),
+        "Roger"
+    );
+    assert_eq!(r.get(r#"friends.#(age>40 && age<50)#|#"#)?.unwrap(), 2);
+
+    assert_eq!(r.get("friends.#(!(age>40)"), Err(ajson::Error::Path));
+    assert_eq!(r.get("friends.#(age>45))"), Err(ajson::Error::Path));
+    assert_eq!(r.get("friends.#(age>45))#.first"), Err(ajson::Error::Path));
+    assert_eq!(r.get("friends.#((age>40).first"), Err(ajson::Error::Path));
+    assert_eq!(r.get("friends.#(&&)"), Err(ajson::Error::Path));
+    assert_eq!(r.get("friends.#(||)#"), Err(ajson::Error::Path));
+    assert_eq!(r.get("friends.#(age>40 &&)"), Err(ajson::Error::Path));
+    assert_eq!(r.get("friends.#(!)"), Err(ajson::Error::Path));
+    assert_eq!(r.get("friends.#(age>)"), Err(ajson::Error::Path));
+
+    Ok(())
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_query_regex() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+    assert_eq!(
+        r.get(r#"friends.#(first=~/^[DJ]a/)#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Dale", "Jane"]
+    );
+    assert_eq!(r.get(r#"friends.#(first=~/^r/i).last"#)?.unwrap(), "Craig");
+    assert!(r.get(r#"friends.#(first=~/^r/).last"#)?.is_none());
+    assert_eq!(
+        r.get(r#"friends.#(last!~"^Mur")#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Roger"]
+    );
+    assert_eq!(
+        r.get(r#"friends.#(first=~/(e|y)$/ && age>45)#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Jane"]
+    );
+    assert_eq!(r.get(r#"children.#(=~/^[^)]+k$/)"#)?.unwrap(), "Jack");
+    assert!(r.get(r#"friends.#(first=~/[/)"#).is_err());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "regex"))]
+#[test]
+fn test_query_regex_disabled() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+    assert_eq!(
+        r.get(r#"friends.#(first=~/^[DJ]a/)#.first"#),
+        Err(ajson::Error::Path)
+    );
+    assert_eq!(
+        r.get(r#"friends.#(last!~"^Mur")#.first"#),
+        Err(ajson::Error::Path)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_query_in() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+    assert_eq!(
+        r.get(r#"friends.#(last in ["Murphy","Craig"])#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Dale", "Roger", "Jane"]
+    );
+    assert_eq!(
+        r.get(r#"friends.#(age in [44, 47])#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Dale", "Jane"]
+    );
+    assert_eq!(
+        r.get(r#"friends.#(age !in [44, 47])#.first"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Roger"]
+    );
+    assert_eq!(
+        r.get(r#"children.#(in ["Sara","Jack"])#"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["Sara", "Jack"]
+    );
+    assert_eq!(
+        r.get(r#"friends.#(first=="Dale" && age in [44, 45]).last"#)?
+            .unwrap(),
+        "Murphy"
+    );
+
+    // the types must match
+    assert_eq!(r.get(r#"friends.#(age in ["44"])#|#"#)?.unwrap(), 0);
+
+    // `in` is a key before an operator
+    let json = r#"{"list":[{"in":1},{"in":2}]}"#;
+    assert_eq!(get(json, "list.#(in == 2).in")?.unwrap(), 2);
+    assert_eq!(get(json, "list.#(in != 2)#|#")?.unwrap(), 1);
+
+    // the operand must be an array
+    assert_eq!(r.get("friends.#(age in 44)"), Err(ajson::Error::Path));
+    assert_eq!(
+        r.get(r#"friends.#(last !in "Murphy")#"#),
+        Err(ajson::Error::Path)
+    );
+
+    let json = r#"[{"a":true},{"a":null},{"a":1},{"a":"x"}]"#;
+    assert_eq!(get(json, "#(a in [true, null])#|#")?.unwrap(), 2);
+    assert_eq!(
+        get(json, "#(a !in [true, null])#.a")?
+            .unwrap()
+            .as_vec()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_query_path_value() -> Result<()> {
+    let json = r#"{
+        "currentTeam": "blue",
+        "limit": 50,
+        "items": [
+            {"name": "a", "price": 10, "cost": 12, "team": "red"},
+            {"name": "b", "price": 30, "cost": 20, "team": "blue"},
+            {"name": "c", "price": 60, "cost": 40, "team": "blue"},
+            {"name": "d", "price": 5, "cost": 5}
+        ]
+    }"#;
+
+    assert_eq!(
+        get(json, "items.#(price>@.cost)#.name")?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["b", "c"]
+    );
+    assert_eq!(get(json, "items.#(price==@.cost).name")?.unwrap(), "d");
+    assert_eq!(
+        get(json, "items.#(team==$.currentTeam)#.name")?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["b", "c"]
+    );
+    assert_eq!(
+        get(json, "items.#(price>$.limit || team!=$.currentTeam)#.name")?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["a", "c"]
+    );
+    assert_eq!(get(json, "items.#(price>$.missing)#|#")?.unwrap(), 0);
+
+    // the root of a value is the value itself
+    let items = get(json, "items")?.unwrap();
+    assert_eq!(items.get("#(price>$.0.price)#|#")?.unwrap(), 2);
+
+    // escaped strings are compared unescaped
+    assert_eq!(
+        get(r#"[{"a":"x\"y","b":1}]"#, r#"#(a=="x\"y").b"#)?.unwrap(),
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_literals() -> Result<()> {
+    let r = parse(BASIC_JSON2)?.unwrap();
+
+    let obj = r#"{"version":!2,"ok":!true,"none":!null,"name":name.first}"#;
+    assert_eq!(r.get(&format!("{}.version", obj))?.unwrap(), 2);
+    assert_eq!(r.get(&format!("{}.ok", obj))?.unwrap(), true);
+    assert!(r.get(&format!("{}.none", obj))?.unwrap().is_null());
+    assert_eq!(r.get(&format!("{}.name", obj))?.unwrap(), "Tom");
+
+    let v = r.get(r#"[!"a,b",!-1.5e3,!{"x":[1,2]},age]"#)?.unwrap();
+    let v = v.as_vec().unwrap();
+    assert_eq!(v[0], "a,b");
+    assert_eq!(v[1], -1500.0);
+    assert_eq!(v[2].get("x.1")?.unwrap(), 2);
+    assert_eq!(v[3], 37);
+
+    assert_eq!(r.get("!true")?.unwrap(), true);
+    assert_eq!(get("", r#"!"text""#)?.unwrap(), "text");
+    assert_eq!(r.get(r#"!{"a":[1,2]}.a.1"#)?.unwrap(), 2);
+    assert_eq!(r.get("![1,2,3]|#")?.unwrap(), 3);
+
+    // not a literal
+    assert!(r.get("!name")?.is_none());
+    assert_eq!(get(r#"{"!x1":5}"#, "!x1")?.unwrap(), 5);
+    assert_eq!(get(r#"{"!1x":5}"#, "!1x")?.unwrap(), 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_query_object() -> Result<()> {
+    let json = r#"{
+        "users": {
+            "u1": {"name": "Dale", "age": 44, "role": "admin"},
+            "u2": {"name": "Roger", "age": 68, "role": "user"},
+            "u3": {"name": "Jane", "age": 47, "role": "admin"}
+        }
+    }"#;
+
+    assert_eq!(
+        get(json, r#"users.#(role=="user").name"#)?.unwrap(),
+        "Roger"
+    );
+    assert_eq!(
+        get(json, r#"users.#(age>45).age"#)?.unwrap().as_i64(),
+        Some(68)
+    );
+    assert!(get(json, "users.#(age>100)")?.is_none());
+
+    let mut keys: Vec<String> = get(json, "users.#(age>45)#|@keys")?
+        .unwrap()
+        .as_vec()
+        .unwrap()
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    keys.sort();
+    assert_eq!(keys, vec!["u2", "u3"]);
+
+    assert_eq!(
+        get(json, r#"users.#(role=="admin")#.name|u3"#)?.unwrap(),
+        "Jane"
+    );
+    assert!(get(json, r#"users.#(role=="admin")#.name|u2"#)?.is_none());
+    assert_eq!(
+        get(json, "users.#(age>100)#|@keys")?
+            .unwrap()
+            .as_vec()
+            .unwrap()
+            .len(),
+        0
+    );
+
+    // constructed objects
+    assert_eq!(get(json, r#"users.@this|#(age<45).name"#)?.unwrap(), "Dale");
+
+    Ok(())
+}
+
+#[test]
+fn test_case_insensitive() -> Result<()> {
+    let json = r#"{
+        "UserID": 1,
+        "Straße": "a",
+        "events": [
+            {"Type": "Click", "name": "ÉTÉ"},
+            {"type": "VIEW", "name": "ete"},
+            {"TYPE": "click", "name": "été"}
+        ]
+    }"#;
+
+    assert_eq!(get(json, "userid~")?.unwrap(), 1);
+    assert!(get(json, "userid")?.is_none());
+    assert_eq!(get(json, "STRASSE~")?.unwrap(), "a");
+    assert_eq!(
+        get(json, "events.#.type~")?.unwrap().as_vec().unwrap(),
+        vec!["Click", "VIEW", "click"]
+    );
+    assert_eq!(get(json, "user\\~")?, None);
+    assert_eq!(get(r#"{"a~":1,"a":2}"#, "a\\~")?.unwrap(), 1);
+
+    assert_eq!(get(json, r#"events.#(name~="été")#|#"#)?.unwrap(), 2);
+    assert_eq!(
+        get(json, r#"events.#(name!~="été")#.name"#)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["ete"]
+    );
+    assert_eq!(get(json, r#"events.#(name~="ete")#|#"#)?.unwrap(), 1);
+
+    let opts = Options::new().case_insensitive(true);
+    assert_eq!(get_with(json, "USERID", opts)?.unwrap(), 1);
+    assert_eq!(
+        get_with(json, r#"events.#(type=="click")#.name"#, opts)?
+            .unwrap()
+            .as_vec()
+            .unwrap(),
+        vec!["ÉTÉ", "été"]
+    );
+    assert_eq!(
+        get_with(json, r#"events.#(type in ["click"])#|#"#, opts)?.unwrap(),
+        2
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_case_insensitive_like() -> Result<()> {
+    let json = r#"[{"name": "Dale"}, {"name": "dan"}, {"name": "Roger"}]"#;
+
+    assert_eq!(get(json, r#"#(name~%"DA*")#|#"#)?.unwrap(), 2);
+    assert_eq!(get(json, r#"#(name!~%"da*").name"#)?.unwrap(), "Roger");
+    assert_eq!(
+        get_with(
+            json,
+            r#"#(name%"d*")#|#"#,
+            Options::new().case_insensitive(true)
+        )?
+        .unwrap(),
+        2
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_wildcard() -> Result<()> {
+    let json = r#"{"child": 1, "children": 2, "xa": 3, "a*b": 4, "list": ["ab", "b", "a"]}"#;
+
+    assert_eq!(get(json, "child*")?.unwrap(), 1);
+    assert_eq!(get(json, "chil?ren")?.unwrap(), 2);
+    assert_eq!(get(json, "a*")?.unwrap(), 4);
+    assert_eq!(get(json, r"a\*b")?.unwrap(), 4);
+    assert!(get(json, r"a\*")?.is_none());
+    assert!(get(json, "?")?.is_none());
+
+    assert_eq!(
+        get(json, r#"list.#(%"a*")#"#)?.unwrap().as_vec().unwrap(),
+        vec!["ab", "a"]
+    );
+    assert_eq!(get(json, r#"list.#(!%"*b")"#)?.unwrap(), "a");
+
+    Ok(())
+}
+
+#[test]
+fn test_selector_order() -> Result<()> {
+    let json = r#"{"name": {"first": "Tom"}, "age": 37, "zip": "10001", "friends": [{"a": 1, "b": 2}, {"c": 3, "a": 4}]}"#;
+
+    let keys = |path| -> Result<Vec<String>> {
+        let keys = get(json, path)?.unwrap();
+        Ok(keys
+            .as_vec()
+            .unwrap()
+            .iter()
+            .map(|k| k.to_string())
+            .collect())
+    };
+
+    for _ in 0..10 {
+        assert_eq!(keys("{zip,name.first,age}|@keys")?, ["zip", "first", "age"]);
+    }
+    assert_eq!(keys("{age,zip,age}|@keys")?, ["age", "zip"]);
+    assert_eq!(keys("{z:age,y:age,x:age}|@reverse|@keys")?, ["x", "y", "z"]);
+    assert_eq!(keys("friends.@join|@keys")?, ["a", "b", "c"]);
+    assert_eq!(get(json, "friends.@join.a")?.unwrap(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_constructed_json() -> Result<()> {
+    let json = r#"{"a": "x\"y\\z\n", "list": ["p\"q", "r"], "o": {"k\"": "v\t"}}"#;
+
+    let paths = [
+        r#"[a,list.0]"#,
+        r#"{a,"x\"y":list,o}"#,
+        r#"list.#(%"p*")#"#,
+        r#"o|@keys"#,
+        r#"o.@values"#,
+        r#"[list.#,o,{a:a}]"#,
+        r#"o.#(%"v*")#"#,
+        r#"[!"q\"",!1]"#,
+    ];
+    for path in paths.iter() {
+        let v = get(json, path)?.unwrap();
+        let s = v.to_string();
+        assert!(
+            serde_json::from_str::<serde_json::Value>(&s).is_ok(),
+            "{}",
+            s
+        );
+    }
+
+    assert_eq!(
+        get(json, "[a,list.0]")?.unwrap().to_string(),
+        r#"["x\"y\\z\n","p\"q"]"#
+    );
+    assert_eq!(
+        get(json, r#"{a,"x\"y":list.1}"#)?.unwrap().to_string(),
+        r#"{"a":"x\"y\\z\n","x\"y":"r"}"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_set() -> Result<()> {
+    let json = r#"{
+  "name": {"first": "Tom", "last": "Anderson"},
+  "children": ["Sara", "Alex"],
+  "fav.movie": "Deer Hunter",
+  "empty": {}
+}"#;
+
+    let s = ajson::set(json, "name.last", "Smith")?;
+    assert_eq!(s, json.replace("Anderson", "Smith"));
+    assert_eq!(get(&s, "name.first")?.unwrap(), "Tom");
+
+    let s = ajson::set(json, "age", 37)?;
+    assert_eq!(get(&s, "age")?.unwrap(), 37);
+    assert!(s.starts_with(&json[..json.len() - 2]));
+
+    let s = ajson::set(json, "children.1", "Jack")?;
+    assert_eq!(get(&s, "children.1")?.unwrap(), "Jack");
+    let s = ajson::set(json, "children.3", true)?;
+    assert_eq!(
+        get(&s, "children")?.unwrap().to_string(),
+        r#"["Sara", "Alex", null, true]"#
+    );
+
+    let s = ajson::set(json, r"fav\.movie", "Rocky \"II\"")?;
+    assert_eq!(get(&s, r"fav\.movie")?.unwrap(), "Rocky \"II\"");
+    let s = ajson::set(json, r"a\.b.c", 1.5)?;
+    assert_eq!(get(&s, r"a\.b.c")?.unwrap(), 1.5);
+
+    let s = ajson::set(json, "empty.x.1.y", "z")?;
+    assert_eq!(
+        get(&s, "empty")?.unwrap().to_string(),
+        r#"{"x":[null,{"y":"z"}]}"#
+    );
+    let s = ajson::set(json, "name.first.nick", "T")?;
+    assert_eq!(get(&s, "name.first.nick")?.unwrap(), "T");
+
+    assert_eq!(ajson::set("", "a.0", Value::Null)?, r#"{"a":[null]}"#);
+    assert_eq!(ajson::set_raw("[]", "0", r#"{"b":[1]}"#)?, r#"[{"b":[1]}]"#);
+    assert_eq!(ajson::set(r#"{"a":1}"#, r"a\~", 2)?, r#"{"a":1,"a~":2}"#);
+
+    for path in [
+        "children.#",
+        "child*",
+        "children.#(=\"Sara\")",
+        "name.FIRST~",
+        "",
+        "children.x",
+    ]
+    .iter()
+    {
+        assert_eq!(ajson::set(json, path, 1), Err(ajson::Error::Path));
+    }
+
+    // the bytes before the value aren't dropped
+    assert_eq!(
+        ajson::set(r#"garbage{"a":1}"#, "a", 2),
+        Err(ajson::Error::Path)
+    );
+    assert_eq!(ajson::insert("x[1]", "0", 2), Err(ajson::Error::Path));
+
+    // the unclosed containers can't be added to
+    assert_eq!(ajson::set(r#"{"a":1"#, "b", 1), Err(ajson::Error::Object));
+    assert_eq!(ajson::set("[1,2", "-1", 3), Err(ajson::Error::Array));
+    assert_eq!(
+        ajson::set(r#"{"a":[1"#, "a.-1", 2),
+        Err(ajson::Error::Array)
+    );
+    assert_eq!(ajson::set(r#"{"a":1"#, "a", 2)?, r#"{"a":2"#);
+
+    Ok(())
+}
+
+#[test]
+fn test_delete() -> Result<()> {
+    let json = r#"{
+  "name": {"first": "Tom", "last": "Anderson"},
+  "children": ["Sara", "Alex", "Jack"],
+  "fav.movie": "Deer Hunter",
+  "friends": [
+    {"first": "Dale", "last": "Murphy", "age": 44},
+    {"first": "Roger", "last": "Craig", "age": 68},
+    {"first": "Jane", "last": "Murphy", "age": 47}
+  ]
+}"#;
+
+    let s = ajson::delete(json, "name.first")?;
+    assert!(s.contains(r#""name": {"last": "Anderson"}"#));
+    let s = ajson::delete(json, "name.last")?;
+    assert!(s.contains(r#""name": {"first": "Tom"}"#));
+    let s = ajson::delete(json, "friends")?;
+    assert!(s.ends_with("\"Deer Hunter\"\n}"));
+    let s = ajson::delete(json, r"fav\.movie")?;
+    assert!(get(&s, r"fav\.movie")?.is_none());
+    assert!(get(&s, "friends")?.is_some());
+
+    let children = |s: &str| get(s, "children").map(|v| v.unwrap().to_string());
+    assert_eq!(
+        children(&ajson::delete(json, "children.0")?)?,
+        r#"["Alex", "Jack"]"#
+    );
+    assert_eq!(
+        children(&ajson::delete(json, "children.-1")?)?,
+        r#"["Sara", "Alex"]"#
+    );
+    assert_eq!(
+        children(&ajson::delete(json, r#"children.#(%"*a")#"#)?)?,
+        r#"["Alex", "Jack"]"#
+    );
+    assert_eq!(
+        children(&ajson::delete(json, r#"children.#(!="Alex")#"#)?)?,
+        r#"["Alex"]"#
+    );
+    assert_eq!(
+        children(&ajson::delete(json, r#"children.#(%"*")#"#)?)?,
+        "[]"
+    );
+
+    let s = ajson::delete(json, r#"friends.#(last=="Murphy")#"#)?;
+    assert_eq!(
+        get(&s, "friends.#.first")?.unwrap().to_string(),
+        r#"["Roger"]"#
+    );
+    let s = ajson::delete(json, r#"friends.#(last=="Murphy")"#)?;
+    assert_eq!(
+        get(&s, "friends.#.first")?.unwrap().to_string(),
+        r#"["Roger","Jane"]"#
+    );
+    let s = ajson::delete(json, "friends.#.age")?;
+    assert_eq!(get(&s, "friends.#.age")?.unwrap().to_string(), "[]");
+    let s = ajson::delete(json, "friends.#(age>45)#.last")?;
+    assert_eq!(
+        get(&s, "friends.#.last")?.unwrap().to_string(),
+        r#"["Murphy"]"#
+    );
+    let s = ajson::delete(json, "name.*")?;
+    assert!(s.contains(r#""name": {}"#));
+
+    for s in [
+        ajson::delete(json, "name.*")?,
+        ajson::delete(json, "friends.#(age>45)#")?,
+    ]
+    .iter()
+    {
+        assert!(
+            serde_json::from_str::<serde_json::Value>(s).is_ok(),
+            "{}",
+            s
+        );
+    }
+    assert_eq!(ajson::delete(json, "nothing.here")?, json);
+    assert_eq!(ajson::delete(json, "children.#"), Err(ajson::Error::Path));
+
+    Ok(())
+}
+
+#[test]
+fn test_append_and_insert() -> Result<()> {
+    let json = r#"{"items": [1, 2, 3], "empty": [], "obj": {"a": 1}}"#;
+
+    let items = |s: &str| get(s, "items").map(|v| v.unwrap().to_string());
+    assert_eq!(items(&ajson::set(json, "items.-1", 4)?)?, "[1, 2, 3, 4]");
+    assert_eq!(items(&ajson::insert(json, "items.0", 0)?)?, "[0,1, 2, 3]");
+    assert_eq!(
+        items(&ajson::insert(json, "items.2", "x")?)?,
+        r#"[1, 2, "x", 3]"#
+    );
+    assert_eq!(items(&ajson::insert(json, "items.3", 4)?)?, "[1, 2, 3, 4]");
+    assert_eq!(items(&ajson::insert(json, "items.-1", 4)?)?, "[1, 2, 3, 4]");
+    assert_eq!(
+        items(&ajson::insert(json, "items.5", 4)?)?,
+        "[1, 2, 3, null, null, 4]"
+    );
+    assert_eq!(
+        items(&ajson::insert_raw(json, "items.1", r#"{"a":[1]}"#)?)?,
+        r#"[1, {"a":[1]}, 2, 3]"#
+    );
+
+    let s = ajson::set(json, "empty.-1", true)?;
+    assert_eq!(get(&s, "empty")?.unwrap().to_string(), "[true]");
+    let s = ajson::insert(json, "empty.0", true)?;
+    assert_eq!(get(&s, "empty")?.unwrap().to_string(), "[true]");
+    let s = ajson::set(json, "new.-1.a", 1)?;
+    assert_eq!(get(&s, "new")?.unwrap().to_string(), r#"[{"a":1}]"#);
+    let s = ajson::insert(json, "new.list.1", 1)?;
+    assert_eq!(get(&s, "new.list")?.unwrap().to_string(), "[null,1]");
+
+    let pretty = "[\n  1,\n  2\n]";
+    assert_eq!(ajson::insert(pretty, "1", 9)?, "[\n  1,\n  9,\n  2\n]");
+    assert_eq!(ajson::set(pretty, "-1", 9)?, "[\n  1,\n  2,\n  9\n]");
+
+    assert_eq!(ajson::insert(json, "obj.0", 1), Err(ajson::Error::Path));
+    assert_eq!(ajson::insert(json, "items.x", 1), Err(ajson::Error::Path));
+    assert_eq!(ajson::insert(json, "items.-2", 1), Err(ajson::Error::Path));
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch() -> Result<()> {
+    use ajson::{patch, Error};
+
+    let apply = |json: &str, patch: &str| -> Result<serde_json::Value> {
+        let s = patch::apply(json, patch)?;
+        Ok(serde_json::from_str(&s).expect(&s))
+    };
+    let json = |s: &str| -> serde_json::Value { serde_json::from_str(s).unwrap() };
+
+    // the examples of RFC 6902
+    assert_eq!(
+        apply(
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#
+        )?,
+        json(r#"{"baz": "qux", "foo": "bar"}"#)
+    );
+    assert_eq!(
+        apply(
+            r#"{"foo": ["bar", "baz"]}"#,
+            r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#
+        )?,
+        json(r#"{"foo": ["bar", "qux", "baz"]}"#)
+    );
+    assert_eq!(
+        apply(
+            r#"{"baz": "qux", "foo": "bar"}"#,
+            r#"[{"op": "remove", "path": "/baz"}]"#
+        )?,
+        json(r#"{"foo": "bar"}"#)
+    );
+    assert_eq!(
+        apply(
+            r#"{"foo": ["bar", "qux", "baz"]}"#,
+            r#"[{"op": "remove", "path": "/foo/1"}]"#
+        )?,
+        json(r#"{"foo": ["bar", "baz"]}"#)
+    );
+    assert_eq!(
+        apply(
+            r#"{"baz": "qux", "foo": "bar"}"#,
+            r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#
+        )?,
+        json(r#"{"baz": "boo", "foo": "bar"}"#)
+    );
+    assert_eq!(
+        apply(
+            r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+            r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#
+        )?,
+        json(r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#)
+    );
+    assert_eq!(
+        apply(
+            r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+            r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#
+        )?,
+        json(r#"{"foo": ["all", "cows", "eat", "grass"]}"#)
+    );
+    assert_eq!(
+        apply(
+            r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#,
+            r#"[{"op": "test", "path": "/baz", "value": "qux"}, {"op": "test", "path": "/foo/1", "value": 2}]"#
+        )?,
+        json(r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#)
+    );
+    assert_eq!(
+        patch::apply(
+            r#"{"baz": "qux"}"#,
+            r#"[{"op": "test", "path": "/baz", "value": "bar"}]"#
+        ),
+        Err(Error::Test)
+    );
+    assert_eq!(
+        apply(
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#
+        )?,
+        json(r#"{"foo": "bar", "child": {"grandchild": {}}}"#)
+    );
+    assert_eq!(
+        patch::apply(
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#
+        ),
+        Err(Error::Path)
+    );
+    assert_eq!(
+        apply(
+            r#"{"/": 9, "~1": 10}"#,
+            r#"[{"op": "test", "path": "/~01", "value": 10}]"#
+        )?,
+        json(r#"{"/": 9, "~1": 10}"#)
+    );
+    assert_eq!(
+        apply(
+            r#"{"foo": ["bar"]}"#,
+            r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#
+        )?,
+        json(r#"{"foo": ["bar", ["abc", "def"]]}"#)
+    );
+
+    // the values are compared semantically
+    let doc = r#"{"n": 1.0, "o": {"a": 1, "b": [1, "x"]}, "s": "A"}"#;
+    let test = r#"[{"op": "test", "path": "/n", "value": 1},
+                   {"op": "test", "path": "/o", "value": {"b": [1e0, "x"], "a": 1}},
+                   {"op": "test", "path": "/s", "value": "A"}]"#;
+    assert_eq!(patch::apply(doc, test)?, doc);
+
+    // all or nothing
+    let doc = r#"{"a": 1}"#;
+    let ops = r#"[{"op": "add", "path": "/b", "value": 2}, {"op": "remove", "path": "/c"}]"#;
+    assert_eq!(patch::apply(doc, ops), Err(Error::Path));
+    let ops =
+        r#"[{"op": "add", "path": "/b", "value": 2}, {"op": "test", "path": "/b", "value": 3}]"#;
+    assert_eq!(patch::apply(doc, ops), Err(Error::Test));
+
+    // the whole document
+    assert_eq!(
+        patch::apply(doc, r#"[{"op": "replace", "path": "", "value": [1]}]"#)?,
+        "[1]"
+    );
+    assert_eq!(
+        patch::apply(doc, r#"[{"op": "move", "from": "", "path": "/a/b"}]"#),
+        Err(Error::Path)
+    );
+
+    for ops in [
+        r#"{}"#,
+        r#"[{"op": "nope", "path": "/a"}]"#,
+        r#"[{"op": "add", "path": "/a"}]"#,
+        r#"[{"op": "add", "path": "a", "value": 1}]"#,
+        r#"[{"op": "remove", "path": "/~2"}]"#,
+    ]
+    .iter()
+    {
+        assert_eq!(patch::apply(doc, ops), Err(Error::Patch), "{}", ops);
+    }
+    assert_eq!(
+        patch::apply(r#"[1]"#, r#"[{"op": "add", "path": "/01", "value": 1}]"#),
+        Err(Error::Path)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_merge_patch() -> Result<()> {
+    use ajson::merge_patch;
+
+    let json = |s: &str| -> serde_json::Value { serde_json::from_str(s).expect(s) };
+
+    // the examples of RFC 7396
+    let cases = [
+        (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+        (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+        (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+        (
+            r#"{"a":{"b":"c"}}"#,
+            r#"{"a":{"b":"d","c":null}}"#,
+            r#"{"a":{"b":"d"}}"#,
+        ),
+        (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+        (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+        (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+        (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+        (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+        (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+        (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+        (
+            r#"{}"#,
+            r#"{"a":{"bb":{"ccc":null}}}"#,
+            r#"{"a":{"bb":{}}}"#,
+        ),
+    ];
+    for (target, patch, result) in cases.iter() {
+        let patched = merge_patch::apply(target, patch)?;
+        assert_eq!(json(&patched), json(result), "{} {}", target, patch);
+    }
+
+    // the formatting of the target is kept
+    let target = r#"{
+    "title": "Goodbye!",
+    "author": {"givenName": "John", "familyName": "Doe"},
+    "tags": ["example", "sample"],
+    "content": "This will be unchanged"
+}"#;
+    let patch = r#"{
+    "title": "Hello!",
+    "phoneNumber": "+01-123-456-7890",
+    "author": {"familyName": null},
+    "tags": ["example"]
+}"#;
+    assert_eq!(
+        merge_patch::apply(target, patch)?,
+        r#"{
+    "title": "Hello!",
+    "author": {"givenName": "John"},
+    "tags": ["example"],
+    "content": "This will be unchanged",
+    "phoneNumber":"+01-123-456-7890"
+}"#
+    );
+
+    // escaped keys are matched by their unescaped text
+    assert_eq!(
+        merge_patch::apply(r#"{"a\u0062": 1, "c": 2}"#, r#"{"ab": null}"#)?,
+        r#"{"c": 2}"#
+    );
+
+    // the diff turns one into the other, the numbers equal in value are
+    // left out
+    let b = r#"{"a": 1.0, "b": {"c": [1, 2], "d": "x"}, "e": true}"#;
+    let a = r#"{"a": 1, "b": {"c": [1, 2], "d": "y", "f": 0}, "g": {}}"#;
+    let diff = merge_patch::diff(a, b)?;
+    assert_eq!(diff, r#"{"b":{"d":"x","f":null},"e":true,"g":null}"#);
+    assert_eq!(
+        merge_patch::apply(a, &diff)?,
+        r#"{"a": 1, "b": {"c": [1, 2], "d": "x"}, "e":true}"#
+    );
+
+    assert_eq!(merge_patch::diff(b, b)?, "{}");
+    assert_eq!(merge_patch::diff(r#"{"a":1}"#, "[1, 2]")?, "[1, 2]");
+    assert_eq!(merge_patch::diff("[1]", r#"{"a":1}"#)?, r#"{"a":1}"#);
+
+    Ok(())
+}
+
+#[test]
+fn test_diff() -> Result<()> {
+    use ajson::{diff, diff_with, ChangeKind, DiffOptions};
+
+    let a = r#"{
+        "name": "Tom",
+        "age": 37,
+        "weight": 70.0,
+        "fav.movie": "Deer Hunter",
+        "a*b?": {"c|d": 1},
+        "children": ["Sara", "Alex"],
+        "address": {"city": "Paris", "zip": "75001"},
+        "friends": [{"id": "d", "age": 44}, {"id": "r", "age": 68}]
+    }"#;
+    let b = r#"{
+        "name": "Tom",
+        "age": "37",
+        "weight": 70,
+        "fav.movie": "Rocky",
+        "a*b?": {"c|d": 2},
+        "children": ["Sara"],
+        "address": ["Paris"],
+        "friends": [{"id": "r", "age": 69}, {"id": "j", "age": 47}],
+        "nick": null
+    }"#;
+
+    let changes = diff(a, b)?;
+    let kinds: Vec<_> = changes.iter().map(|c| (c.path.as_str(), c.kind)).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("age", ChangeKind::TypeChanged),
+            (r"fav\.movie", ChangeKind::Changed),
+            (r"a\*b\?.c\|d", ChangeKind::Changed),
+            ("children.1", ChangeKind::Removed),
+            ("address", ChangeKind::TypeChanged),
+            ("friends.0.id", ChangeKind::Changed),
+            ("friends.0.age", ChangeKind::Changed),
+            ("friends.1.id", ChangeKind::Changed),
+            ("friends.1.age", ChangeKind::Changed),
+            ("nick", ChangeKind::Added),
+        ]
+    );
+
+    // the paths lead to the old and new values
+    for change in changes.iter() {
+        assert_eq!(get(a, &change.path)?, change.old, "{}", change.path);
+        assert_eq!(get(b, &change.path)?, change.new, "{}", change.path);
+    }
+    assert_eq!(changes[0].old.clone().unwrap(), 37_i64);
+    assert_eq!(changes[0].new.clone().unwrap(), "37");
+    assert_eq!(changes[9].new, Some(Value::Null));
+
+    // the arrays keyed by a field
+    let changes = diff_with(a, b, DiffOptions::new().array_key("id"))?;
+    let friends: Vec<_> = changes
+        .iter()
+        .filter(|c| c.path.starts_with("friends"))
+        .map(|c| (c.path.as_str(), c.kind))
+        .collect();
+    assert_eq!(
+        friends,
+        vec![
+            (r#"friends.#(id=="d")"#, ChangeKind::Removed),
+            (r#"friends.#(id=="r").age"#, ChangeKind::Changed),
+            (r#"friends.#(id=="j")"#, ChangeKind::Added),
+        ]
+    );
+    for change in changes.iter() {
+        assert_eq!(get(a, &change.path)?, change.old, "{}", change.path);
+        assert_eq!(get(b, &change.path)?, change.new, "{}", change.path);
+    }
+
+    // arrays without the field are compared by positions
+    let changes = diff_with("[1, 2]", "[2, 1]", DiffOptions::new().array_key("id"))?;
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].path, "0");
+
+    assert_eq!(diff(a, a)?, vec![]);
+    assert_eq!(diff("[1.0, 2e1]", "[1, 20]")?, vec![]);
+
+    let changes = diff("1", "2")?;
+    assert_eq!(changes[0].path, "");
+    assert_eq!(changes[0].kind, ChangeKind::Changed);
+
+    Ok(())
+}
+
+#[test]
+fn test_redact() -> Result<()> {
+    use ajson::{redact, Error, Mask};
+
+    let json = r#"{
+    "user": {"name": "Tom", "email": "tom@example.com", "password": "s3cret"},
+    "admin": {"name": "Jim", "password": "hunter2", "pin": 1234},
+    "tokens": [{"kind": "api", "value": "abc"}, {"kind": "web", "value": "def"}],
+    "note": "keep \"this\""
+}"#;
+
+    let redacted = redact(
+        json,
+        &["*.password", "user.email", "tokens.#(kind==\"api\")#.value"],
+        Mask::Text("***"),
+    )?;
+    assert_eq!(
+        redacted,
+        r#"{
+    "user": {"name": "Tom", "email": "***", "password": "***"},
+    "admin": {"name": "Jim", "password": "***", "pin": 1234},
+    "tokens": [{"kind": "api", "value": "***"}, {"kind": "web", "value": "def"}],
+    "note": "keep \"this\""
+}"#
+    );
+
+    // the nested and repeated matches are masked once
+    let redacted = redact(
+        json,
+        &["admin", "admin.pin", "tokens.#.value"],
+        Mask::Placeholder,
+    )?;
+    assert_eq!(get(&redacted, "admin")?.unwrap().to_string(), "{}");
+    assert_eq!(
+        get(&redacted, "tokens.#.value")?.unwrap().to_string(),
+        r#"["",""]"#
+    );
+    assert_eq!(get(&redacted, "user.password")?.unwrap(), "s3cret");
+
+    // the hashes have a fixed length, and the equal values share one
+    let redacted = redact(
+        r#"["abc", "abc", 1, "a very long value"]"#,
+        &["#"],
+        Mask::Hash,
+    );
+    assert_eq!(redacted, Err(Error::Path));
+    let redacted = redact(
+        r#"["abc", "abc", 1, "a very long value"]"#,
+        &["0", "1", "2", "3"],
+        Mask::Hash,
+    )?;
+    let hashes: Vec<_> = get(&redacted, "@this")?
+        .unwrap()
+        .as_vec()
+        .unwrap()
+        .iter()
+        .map(|v| v.to_string())
+        .collect();
+    assert!(hashes.iter().all(|h| h.len() == 16));
+    assert_eq!(hashes[0], hashes[1]);
+    assert_ne!(hashes[1], hashes[2]);
+
+    // the mask is escaped, nothing else changes when nothing matches
+    assert_eq!(
+        redact(r#"{"a": 1}"#, &["a"], Mask::Text(r#"<"x">"#))?,
+        r#"{"a": "<\"x\">"}"#
+    );
+    assert_eq!(redact(json, &["missing.path"], Mask::Hash)?, json);
+
+    Ok(())
+}
+
+#[test]
+fn test_project() -> Result<()> {
+    use ajson::project;
+
+    let json = r#"{
+    "id": 7,
+    "user": {"name": "Tom", "fav.movie": "Rocky", "age": 37},
+    "items": [
+        {"sku": "a1", "qty": 1, "tags": ["x"]},
+        {"sku": "b2", "qty": 5},
+        {"qty": 2}
+    ],
+    "total": 8
+}"#;
+
+    assert_eq!(
+        project(json, &["id", r"user.fav\.movie", "items.#.sku"])?,
+        r#"{
+    "id": 7,
+    "user": {"fav.movie": "Rocky"},
+    "items": [
+        {"sku": "a1"},
+        {"sku": "b2"}
+    ]
+}"#
+    );
+
+    // the key order comes from the document, not from the paths
+    assert_eq!(
+        project(json, &["total", "items.#(qty>1)#.qty", "user", "user.age"])?,
+        r#"{
+    "user": {"name": "Tom", "fav.movie": "Rocky", "age": 37},
+    "items": [
+        null,
+        {"qty": 5},
+        {"qty": 2}
+    ],
+    "total": 8
+}"#
+    );
+
+    // the indexes of the kept elements don't change
+    assert_eq!(
+        project(r#"[1, [2, 3], 4, 5]"#, &["1.1", "2"])?,
+        "[null, [null, 3], 4]"
+    );
+    assert_eq!(project(r#"[1, 2]"#, &["5"])?, "[]");
+    assert_eq!(project(r#"{"a": 1}"#, &["b"])?, "{}");
+    assert_eq!(project("3", &["a"])?, "3");
+    assert!(project(json, &["items.#"]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_merge() -> Result<()> {
+    use ajson::{merge, ArrayStrategy, MergeOptions};
+
+    let base = r#"{
+    "name": "api",
+    "port": 80,
+    "tls": {"on": false, "cert": "a.pem"},
+    "tags": ["web"],
+    "plugins": ["auth"],
+    "services": [
+        {"name": "db", "ports": [5432]},
+        {"name": "cache", "ports": [6379]}
+    ]
+}"#;
+    let overlay = r#"{
+        "port": 8080,
+        "tls": {"on": true, "ciphers": ["aes"]},
+        "tags": ["prod"],
+        "plugins": ["auth", "rate-limit"],
+        "services": [
+            {"name": "cache", "ports": [6380]},
+            {"name": "queue", "ports": [5672]}
+        ],
+        "debug": false
+    }"#;
+
+    // the arrays are replaced by default
+    let merged = merge(base, overlay, MergeOptions::new())?;
+    assert_eq!(get(&merged, "port")?.unwrap(), 8080_i64);
+    assert_eq!(
+        get(&merged, "tls")?.unwrap().to_string(),
+        r#"{"on": true, "cert": "a.pem", "ciphers":["aes"]}"#
+    );
+    assert_eq!(get(&merged, "tags")?.unwrap().to_string(), r#"["prod"]"#);
+    assert_eq!(get(&merged, "services.#")?.unwrap(), 2_i64);
+    assert_eq!(get(&merged, "debug")?.unwrap(), false);
+    assert!(merged.starts_with("{\n    \"name\": \"api\",\n    \"port\": 8080,"));
+
+    let options = MergeOptions::new()
+        .array("tags", ArrayStrategy::Concat)
+        .array("plugins", ArrayStrategy::UnionBy("id"))
+        .array("services", ArrayStrategy::UnionBy("name"))
+        .array("services.#.ports", ArrayStrategy::Concat);
+    let merged = merge(base, overlay, options)?;
+    assert_eq!(
+        get(&merged, "tags")?.unwrap().to_string(),
+        r#"["web","prod"]"#
+    );
+    assert_eq!(
+        get(&merged, "plugins")?.unwrap().to_string(),
+        r#"["auth","rate-limit"]"#
+    );
+    assert_eq!(
+        get(&merged, "services")?.unwrap().to_string(),
+        r#"[
+        {"name": "db", "ports": [5432]},
+        {"name": "cache", "ports": [6379,6380]},
+        {"name": "queue", "ports": [5672]}
+    ]"#
+    );
+
+    // the conflicts of types
+    fn conflict(path: &str, base: &Value, overlay: &Value) -> Value<'static> {
+        match path {
+            "tls" => base.clone().into_owned(),
+            _ => Value::String(format!("{}|{:?}", path, overlay).into()),
+        }
+    }
+    let options = MergeOptions::new().conflict(conflict);
+    let merged = merge(
+        r#"{"tls": {"on": true}, "port": 80, "a": {"b": "x"}}"#,
+        r#"{"tls": "off", "port": "80", "a": {"b": 1}}"#,
+        options,
+    )?;
+    assert_eq!(
+        merged,
+        r#"{"tls": {"on": true}, "port": "port|\"80\"", "a": {"b": "a.b|1"}}"#
+    );
+
+    // the values which aren't objects are replaced
+    assert_eq!(merge("[1]", "[2]", MergeOptions::new())?, "[2]");
+    assert_eq!(
+        merge(" 1 ", r#"{"a":1}"#, MergeOptions::new())?,
+        r#" {"a":1} "#
+    );
+    assert_eq!(merge(r#"{"a":1}"#, "", MergeOptions::new())?, r#"{"a":1}"#);
+    assert_eq!(merge("", r#"{"a":1}"#, MergeOptions::new())?, r#"{"a":1}"#);
+
+    Ok(())
+}